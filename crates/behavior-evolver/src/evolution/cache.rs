@@ -0,0 +1,100 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::{Hash, Hasher},
+};
+
+use bevy::prelude::*;
+use creature_builder::builder::node::CreatureMorphologyGraph;
+
+use super::{diagnostics::PhysicsDiagnostics, generation::TestResult, map_elites::CreatureTraits, novelty::BehaviorDescriptor};
+
+
+/// A hash of everything about a creature that affects how it behaves, which
+/// excludes its id so that identical copies share a hash
+pub fn morphology_hash(morph: &CreatureMorphologyGraph) -> u64 {
+    let mut nodes: Vec<_> = morph.nodes_map().iter().collect();
+    nodes.sort_unstable_by_key(|(id, _)| id.0);
+    let mut edges: Vec<_> = morph.edges_map().iter().collect();
+    edges.sort_unstable_by_key(|(id, _)| id.0);
+
+    let content = (morph.graph.get_root(), nodes, edges, &morph.root, &morph.brain, &morph.central);
+    let mut hasher = DefaultHasher::new();
+    ron::ser::to_string(&content).expect("Unable to serialize creature").hash(&mut hasher);
+    hasher.finish()
+}
+
+
+/// The results of the tests of a creature, averaged over every time it was
+/// tested
+#[derive(Clone, Debug, Default)]
+pub struct CachedFitness {
+    pub fitness: f32,
+    pub objectives: Vec<f32>,
+    /// The behavior, traits and diagnostics from the latest test
+    pub behavior: BehaviorDescriptor,
+    pub traits: CreatureTraits,
+    pub diagnostics: PhysicsDiagnostics,
+    pub evaluations: usize,
+    /// The generation the creature was last tested in
+    pub tested_generation: usize,
+}
+
+
+/// Carries the fitness of creatures that have already been tested into later
+/// generations instead of testing them again, as happens to every retained
+/// creature. Only used when creatures are tested one at a time
+#[derive(Resource, Clone, Debug, Default)]
+pub struct FitnessCache {
+    /// Tests cached creatures again once this many generations have passed
+    /// since their last test, averaging the results, if set
+    pub reevaluate_interval: Option<usize>,
+    entries: HashMap<u64, CachedFitness>,
+}
+
+impl FitnessCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_reevaluation(mut self, interval: usize) -> Self {
+        self.reevaluate_interval = Some(interval);
+        self
+    }
+
+    /// The cached results of a creature, unless it is due to be tested again
+    pub fn get(&self, hash: u64, generation: usize) -> Option<&CachedFitness> {
+        self.entries
+            .get(&hash)
+            .filter(|cached| self.reevaluate_interval.map_or(true, |interval| generation < cached.tested_generation + interval.max(1)))
+    }
+
+    /// Adds the results of a test to those of the creature, returning the
+    /// averaged results
+    pub fn record(&mut self, hash: u64, generation: usize, test: TestResult) -> &CachedFitness {
+        let cached = self.entries.entry(hash).or_default();
+        let n = cached.evaluations as f32;
+        let average = |old: f32, new: f32| if n == 0.0 { new } else { (old * n + new) / (n + 1.0) };
+        cached.fitness = average(cached.fitness, test.fitness);
+        if cached.objectives.len() != test.objectives.len() {
+            cached.objectives = test.objectives;
+        } else {
+            cached.objectives.iter_mut().zip(test.objectives).for_each(|(old, new)| *old = average(*old, new));
+        }
+        cached.behavior = test.behavior;
+        cached.traits = test.traits;
+        cached.diagnostics = test.diagnostics;
+        cached.evaluations += 1;
+        cached.tested_generation = generation;
+        cached
+    }
+
+    /// Forgets creatures that are no longer in the population
+    pub fn retain(&mut self, hashes: &HashSet<u64>) {
+        self.entries.retain(|hash, _| hashes.contains(hash));
+    }
+
+    /// Forgets every creature, as when the environment or fitness changes
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -0,0 +1,458 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+use bevy_rapier3d::{
+    dynamics::{RigidBody, Velocity},
+    geometry::{Collider, ColliderMassProperties, Friction},
+};
+use creature_builder::{
+    builder::node::CreatureMorphologyGraph, config::CreatureBuilderConfig, limb::CreatureLimb, sensor::ContactFilterTag, CreatureId,
+};
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    fitness::{compete::CompetitionFitnessEval, EvolutionFitnessEval, FitnessEvalInput},
+    generation::{CreatureStateQuery, EvolutionGeneration, GenerationTestingConfig},
+    novelty::BehaviorTracker,
+    state::{EvolutionState, EvolutionTrainingEvent},
+    terrain::Terrain,
+    trajectory::TrajectoryRecorder,
+    write::{load_champions, write_champions},
+    EnvironmentConfig,
+};
+
+
+/// Tests each generation in matches between pairs of creatures over a free
+/// object instead of one creature at a time. Used alongside a
+/// `CreatureEvolutionPlugin<CompetitionFitnessEval>`
+pub struct CompetitionPlugin {
+    pub tournament: Tournament,
+}
+
+impl CompetitionPlugin {
+    pub fn new(tournament: Tournament) -> Self {
+        Self { tournament }
+    }
+}
+
+impl Plugin for CompetitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.tournament.clone())
+            .add_systems(OnEnter(EvolutionState::BeginTrainingSession), begin_tournament)
+            .add_systems(Update, test_competition);
+    }
+}
+
+
+/// The layout of a match
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompetitionArena {
+    /// The distance between the centers of the two creatures at the start
+    pub separation: f32,
+    /// Half the side length of the cube between the creatures
+    pub object_size: f32,
+    pub object_density: f32,
+    pub object_friction: f32,
+}
+
+impl Default for CompetitionArena {
+    fn default() -> Self {
+        Self { separation: 8.0, object_size: 0.5, object_density: 1.0, object_friction: 0.5 }
+    }
+}
+
+impl CompetitionArena {
+    /// Where each side of a match starts and how far it is turned about the
+    /// vertical axis, so that the two face each other over the object
+    pub fn placement(&self, side: usize) -> (Vec3, f32) {
+        let x = self.separation / 2.0;
+        if side == 0 {
+            (Vec3::new(-x, 0.0, 0.0), 0.0)
+        } else {
+            (Vec3::new(x, 0.0, 0.0), PI)
+        }
+    }
+
+    /// Where the object starts, resting on the terrain if there is one
+    pub fn object_transform(&self, terrain: Option<&Terrain>) -> Transform {
+        let ground = terrain.map_or(0.0, |terrain| terrain.height_at(0.0, 0.0));
+        Transform::from_xyz(0.0, ground + self.object_size, 0.0)
+    }
+}
+
+
+#[derive(Component)]
+pub struct CompetitionObject;
+
+
+/// Who each creature is matched against
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TournamentOpponents {
+    /// This many opponents from the creature's own generation, paired
+    /// round-robin over the shuffled population
+    Sample(usize),
+    /// The champions of this many of the latest generations, falling back to
+    /// sampled opponents until there are any
+    HallOfFame(usize),
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Contestant {
+    /// A creature of the generation being tested, by index
+    Creature(usize),
+    /// A past champion, by index into `Tournament::champions`
+    Champion(usize),
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompetitionMatch {
+    pub sides: [Contestant; 2],
+}
+
+
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+pub struct Tournament {
+    pub opponents: TournamentOpponents,
+    pub arena: CompetitionArena,
+    /// The fittest creature of each of the latest generations, oldest first
+    pub champions: Vec<CreatureMorphologyGraph>,
+}
+
+impl Tournament {
+    pub fn new(opponents: TournamentOpponents) -> Self {
+        Self { opponents, arena: CompetitionArena::default(), champions: Vec::new() }
+    }
+
+    pub fn with_arena(mut self, arena: CompetitionArena) -> Self {
+        self.arena = arena;
+        self
+    }
+
+    /// The matches that make up the tournament of a generation
+    pub fn schedule<R: Rng>(&self, population: &[CreatureMorphologyGraph], rng: &mut R) -> Vec<CompetitionMatch> {
+        match self.opponents {
+            TournamentOpponents::HallOfFame(count) if !self.champions.is_empty() => {
+                let first = self.champions.len().saturating_sub(count);
+                let mut matches = Vec::new();
+                for (i, creature) in population.iter().enumerate() {
+                    for (j, champion) in self.champions.iter().enumerate().skip(first) {
+                        // A retained champion would share its limbs' ids with itself
+                        if champion.creature != creature.creature {
+                            matches.push(CompetitionMatch { sides: [Contestant::Creature(i), Contestant::Champion(j)] });
+                        }
+                    }
+                }
+                matches
+            },
+            TournamentOpponents::Sample(count) | TournamentOpponents::HallOfFame(count) => round_robin(population.len(), count, rng),
+        }
+    }
+
+    /// Records the fittest creature of a tested generation, keeping as many
+    /// champions as are played against
+    pub fn crown(&mut self, creature: &CreatureMorphologyGraph) {
+        let count = match self.opponents {
+            TournamentOpponents::Sample(count) | TournamentOpponents::HallOfFame(count) => count,
+        };
+        self.champions.push(creature.clone());
+        let excess = self.champions.len().saturating_sub(count);
+        self.champions.drain(..excess);
+    }
+
+    pub fn contestant<'a>(&'a self, population: &'a [CreatureMorphologyGraph], contestant: Contestant) -> &'a CreatureMorphologyGraph {
+        match contestant {
+            Contestant::Creature(i) => &population[i],
+            Contestant::Champion(i) => &self.champions[i],
+        }
+    }
+}
+
+
+/// Pairs each of `n` creatures with `count` others, or with every other
+/// creature if there are too few. When both `n` and `count` are odd one
+/// creature plays an extra match
+fn round_robin<R: Rng>(n: usize, count: usize, rng: &mut R) -> Vec<CompetitionMatch> {
+    let mut matches = Vec::new();
+    if count + 1 >= n {
+        for i in 0..n {
+            for j in i + 1..n {
+                matches.push(CompetitionMatch { sides: [Contestant::Creature(i), Contestant::Creature(j)] });
+            }
+        }
+        return matches;
+    }
+
+    // Each offset around the shuffled ring gives every creature two matches
+    let mut order: Vec<usize> = (0..n).collect();
+    order.shuffle(rng);
+    let pair = |i: usize, offset: usize| CompetitionMatch {
+        sides: [Contestant::Creature(order[i]), Contestant::Creature(order[(i + offset) % n])],
+    };
+    for offset in 1..=count / 2 {
+        matches.extend((0..n).map(|i| pair(i, offset)));
+    }
+
+    // An odd count takes one more match each, pairing creatures across the
+    // ring at an offset no earlier pass used
+    if count % 2 == 1 {
+        let half = n / 2;
+        matches.extend((0..half).map(|i| pair(i, half)));
+        if n % 2 == 1 {
+            matches.push(pair(n - 1, half));
+        }
+    }
+    matches
+}
+
+
+/// The progress through the tournament of the generation being tested
+#[derive(Default)]
+pub(crate) struct TournamentRound {
+    matches: Vec<CompetitionMatch>,
+    current: Option<usize>,
+    fitnesses: [CompetitionFitnessEval; 2],
+    ids: [Option<CreatureId>; 2],
+    /// The creature whose behavior each side is recording, if any
+    tracking: [Option<usize>; 2],
+    /// The summed fitness and objectives of each creature over its matches
+    totals: Vec<(f32, Vec<f32>, usize)>,
+    /// The number of matches each creature has yet to play
+    remaining: Vec<usize>,
+    /// The behavior of each creature, recorded during its first match
+    behaviors: Vec<Option<BehaviorTracker>>,
+    object: Option<Entity>,
+    train_time: usize,
+    waiting_for_fall: bool,
+    fall_wait_time: usize,
+    started: bool,
+}
+
+
+fn begin_tournament(mut tournament: ResMut<Tournament>, config: Res<GenerationTestingConfig>) {
+    if let Some(champions) = load_champions(&config.session) {
+        tournament.champions = champions;
+    }
+}
+
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn test_competition(
+    mut commands: Commands,
+    mut meshes: Option<ResMut<Assets<Mesh>>>,
+    mut materials: Option<ResMut<Assets<StandardMaterial>>>,
+    mut generation: ResMut<EvolutionGeneration<CompetitionFitnessEval>>,
+    mut tournament: ResMut<Tournament>,
+    config: Res<GenerationTestingConfig>,
+    state: Res<State<EvolutionState>>,
+    mut next_state: ResMut<NextState<EvolutionState>>,
+    limbs: Query<(Entity, &CreatureLimb)>,
+    objects: Query<&Transform, With<CompetitionObject>>,
+    creature_state: CreatureStateQuery,
+    mut training_evw: EventWriter<EvolutionTrainingEvent>,
+    mut build_conf: ResMut<CreatureBuilderConfig>,
+    environment: Res<EnvironmentConfig>,
+    mut recorder: Option<ResMut<TrajectoryRecorder>>,
+    mut round: Local<TournamentRound>,
+) {
+    let object_position = objects
+        .get_single()
+        .map_or_else(|_| tournament.arena.object_transform(environment.terrain.as_ref()).translation, |t| t.translation);
+    let inputs = [0, 1].map(|side| match round.ids[side] {
+        Some(id) => creature_state.input(id, round.train_time, config.test_time),
+        None => FitnessEvalInput { test_time: config.test_time, ..Default::default() },
+    });
+
+    match state.get() {
+        EvolutionState::EvaluatingCreature => {
+            match round.current {
+                Some(m) => {
+                    let centers = [&inputs[0], &inputs[1]].map(FitnessEvalInput::center_of_mass);
+                    for side in 0..2 {
+                        round.fitnesses[side].observe(object_position, centers[1 - side]);
+                        let Contestant::Creature(i) = round.matches[m].sides[side] else { continue };
+                        let eval = round.fitnesses[side].final_eval(inputs[side].clone());
+                        let objectives = round.fitnesses[side].final_objectives(inputs[side].clone());
+                        if let Some(recorder) = recorder.as_mut() {
+                            recorder.finish_side(side, generation.population[i].creature.0, round.totals[i].2, eval);
+                        }
+                        let total = &mut round.totals[i];
+                        total.0 += eval;
+                        total.1.resize(objectives.len(), 0.0);
+                        total.1.iter_mut().zip(objectives).for_each(|(sum, objective)| *sum += objective);
+                        total.2 += 1;
+                        round.remaining[i] -= 1;
+                        if round.remaining[i] == 0 {
+                            training_evw.send(EvolutionTrainingEvent::FinishedTestingCreature);
+                        }
+                    }
+
+                    let ids = round.ids;
+                    limbs
+                        .iter()
+                        .filter(|(_, limb)| ids.contains(&Some(limb.creature)))
+                        .for_each(|(entity, _)| commands.entity(entity).despawn());
+                    if let Some(object) = round.object.take() {
+                        commands.entity(object).despawn();
+                    }
+                    round.ids = [None; 2];
+                    round.current = Some(m + 1);
+                },
+                None => {
+                    let n = generation.population.len();
+                    round.matches = tournament.schedule(&generation.population, &mut rand::thread_rng());
+                    round.totals = vec![(0.0, Vec::new(), 0); n];
+                    round.remaining = vec![0; n];
+                    round.behaviors = vec![None; n];
+                    for sides in round.matches.iter().map(|m| m.sides).collect::<Vec<_>>() {
+                        for contestant in sides {
+                            if let Contestant::Creature(i) = contestant {
+                                round.remaining[i] += 1;
+                            }
+                        }
+                    }
+                    // Creatures without an opponent are done before the tournament starts
+                    for _ in round.remaining.iter().filter(|remaining| **remaining == 0) {
+                        training_evw.send(EvolutionTrainingEvent::FinishedTestingCreature);
+                    }
+                    round.current = Some(0);
+                },
+            }
+
+            let m = round.current.unwrap();
+            if m < round.matches.len() {
+                let sides = round.matches[m].sides;
+                for (side, contestant) in sides.into_iter().enumerate() {
+                    let morph = tournament.contestant(&generation.population, contestant);
+                    let mut result = morph.evaluate();
+                    result.align_to_ground();
+                    let (position, turn) = tournament.arena.placement(side);
+                    result.place(position, turn);
+                    match (meshes.as_mut(), materials.as_mut()) {
+                        (Some(meshes), Some(materials)) => {
+                            let color = match contestant {
+                                Contestant::Creature(i) => generation.populate_flags.get(i).map_or(Color::WHITE, |flag| flag.into_color()),
+                                Contestant::Champion(_) => Color::rgba_u8(249, 226, 175, 220),
+                            };
+                            result.build(&mut commands, meshes, materials, color);
+                        },
+                        _ => result.build_nowindow(&mut commands),
+                    }
+
+                    round.ids[side] = Some(morph.creature);
+                    round.tracking[side] = match contestant {
+                        Contestant::Creature(i) if round.behaviors[i].is_none() => {
+                            round.behaviors[i] = Some(BehaviorTracker::default());
+                            Some(i)
+                        },
+                        _ => None,
+                    };
+                    round.fitnesses[side] = generation.new_fitness();
+                }
+
+                let arena = &tournament.arena;
+                let size = arena.object_size;
+                let object = commands
+                    .spawn((
+                        RigidBody::Dynamic,
+                        Velocity::zero(),
+                        Collider::cuboid(size, size, size),
+                        ColliderMassProperties::Density(arena.object_density),
+                        Friction::coefficient(arena.object_friction),
+                        ContactFilterTag::ObjectGroup,
+                        CompetitionObject,
+                        Name::new("Competition Object"),
+                    ))
+                    .id();
+                match (meshes.as_mut(), materials.as_mut()) {
+                    (Some(meshes), Some(materials)) => {
+                        commands.entity(object).insert(PbrBundle {
+                            mesh: meshes.add(Mesh::from(shape::Cube::new(size * 2.0))),
+                            material: materials.add(StandardMaterial::from(Color::rgb(0.9, 0.9, 0.9))),
+                            transform: arena.object_transform(environment.terrain.as_ref()),
+                            ..default()
+                        });
+                    },
+                    _ => {
+                        commands.entity(object).insert((arena.object_transform(environment.terrain.as_ref()), GlobalTransform::default()));
+                    },
+                }
+                round.object = Some(object);
+
+                round.train_time = 0;
+                round.started = false;
+                round.waiting_for_fall = config.wait_for_fall;
+                round.fall_wait_time = 0;
+                if config.wait_for_fall {
+                    build_conf.behavior.disable_behavior = true;
+                }
+                next_state.set(EvolutionState::TestingCreature);
+            } else {
+                let round = &mut *round;
+                let width = round.totals.iter().map(|total| total.1.len()).max().unwrap_or(0);
+                generation.fitnesses = round.totals.iter().map(|(fitness, _, count)| fitness / (*count).max(1) as f32).collect();
+                generation.objectives = round
+                    .totals
+                    .iter_mut()
+                    .map(|(_, objectives, count)| {
+                        objectives.resize(width, 0.0);
+                        objectives.iter().map(|objective| objective / (*count).max(1) as f32).collect()
+                    })
+                    .collect();
+                let trackers: Vec<BehaviorTracker> = round.behaviors.drain(..).map(Option::unwrap_or_default).collect();
+                generation.behaviors = trackers.iter().map(BehaviorTracker::descriptor).collect();
+                generation.traits = trackers.iter().map(|tracker| tracker.traits(environment.timestep)).collect();
+
+                let best = generation.fitnesses.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1)).map(|(i, _)| i);
+                if let Some(best) = best {
+                    let champion = generation.population[best].clone();
+                    tournament.crown(&champion);
+                    write_champions(&config.session, &tournament.champions);
+                }
+
+                round.current = None;
+                next_state.set(EvolutionState::WritingGeneration);
+            }
+        },
+        EvolutionState::TestingCreature => {
+            if round.waiting_for_fall {
+                round.fall_wait_time += 1;
+                let y_vel: f32 = inputs.iter().flat_map(|input| input.limbs.iter()).map(|(_, vel)| vel.linvel.y).sum();
+                if round.fall_wait_time > 30 && (y_vel.abs() < 0.01 || round.fall_wait_time > config.wait_for_fall_timeout) {
+                    round.waiting_for_fall = false;
+                    build_conf.behavior.disable_behavior = false;
+                }
+                return;
+            }
+
+            if !round.started {
+                round.started = true;
+                for (side, input) in inputs.iter().enumerate() {
+                    round.fitnesses[side].eval_start(input.clone());
+                }
+            }
+
+            round.train_time += 1;
+            let centers = [&inputs[0], &inputs[1]].map(FitnessEvalInput::center_of_mass);
+            for (side, input) in inputs.iter().enumerate() {
+                round.fitnesses[side].observe(object_position, centers[1 - side]);
+                round.fitnesses[side].eval_continuous(input.clone());
+                if let Some(tracker) = round.tracking[side].and_then(|i| round.behaviors[i].as_mut()) {
+                    tracker.record(input, input.ground_contact_ratio());
+                }
+                let recorded = round.current.is_some_and(|m| matches!(round.matches[m].sides[side], Contestant::Creature(_)));
+                if let Some(recorder) = recorder.as_mut().filter(|_| recorded) {
+                    recorder.record_side(side, input);
+                }
+            }
+
+            if round.train_time > config.test_time {
+                round.train_time = 0;
+                next_state.set(EvolutionState::EvaluatingCreature);
+            }
+        },
+        _ => (),
+    }
+}
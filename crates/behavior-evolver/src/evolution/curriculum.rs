@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    cache::FitnessCache,
+    fitness::EvolutionFitnessEval,
+    generation::{EvolutionGeneration, GenerationTestingConfig},
+    write::write_environment,
+    EnvironmentConfig,
+};
+
+
+/// One step of a curriculum. Any setting left unset keeps the value from the
+/// previous stage
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CurriculumStage {
+    pub name: String,
+    /// Advance to the next stage once this generation is reached
+    pub until_generation: Option<usize>,
+    /// Advance to the next stage once the best fitness of a generation reaches
+    /// this value
+    pub until_fitness: Option<f32>,
+    pub environment: Option<EnvironmentConfig>,
+    pub test_time: Option<usize>,
+    /// Passed to `EvolutionFitnessEval::set_weights`
+    pub fitness_weights: Option<Vec<f32>>,
+}
+
+
+/// A schedule of environments, test times and fitness weights that changes as
+/// training progresses
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Curriculum {
+    pub stages: Vec<CurriculumStage>,
+    /// The index of the active stage
+    #[serde(skip)]
+    pub current: usize,
+    #[serde(skip)]
+    applied: Option<usize>,
+}
+
+impl Curriculum {
+    pub fn new(stages: Vec<CurriculumStage>) -> Self {
+        Self { stages, current: 0, applied: None }
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        ron::de::from_str(&data).map_err(|e| e.to_string())
+    }
+
+    pub fn current_stage(&self) -> Option<&CurriculumStage> {
+        self.stages.get(self.current)
+    }
+
+    /// Moves to the next stage if the current one is finished, returning
+    /// whether the stage changed
+    pub fn advance(&mut self, generation: usize, best_fitness: f32) -> bool {
+        let Some(stage) = self.current_stage() else { return false };
+        if self.current + 1 >= self.stages.len() {
+            return false;
+        }
+
+        let finished = stage.until_generation.is_some_and(|until| generation >= until)
+            || stage.until_fitness.is_some_and(|until| best_fitness >= until);
+        if finished {
+            self.current += 1;
+        }
+        finished
+    }
+}
+
+
+/// Applies the settings of the current curriculum stage, advancing to the next
+/// stage first if the last generation finished the current one. Runs before
+/// the tested generation is replaced, while its fitnesses are still known
+pub(crate) fn advance_curriculum<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
+    mut curriculum: ResMut<Curriculum>,
+    mut generation: ResMut<EvolutionGeneration<F>>,
+    mut gen_test_conf: ResMut<GenerationTestingConfig>,
+    mut environment: ResMut<EnvironmentConfig>,
+    cache: Option<ResMut<FitnessCache>>,
+) {
+    if !generation.fitnesses.is_empty() {
+        let best_fitness = generation.fitnesses.iter().copied().fold(f32::MIN, f32::max);
+        curriculum.advance(generation.current_generation + 1, best_fitness);
+    }
+    if curriculum.applied == Some(curriculum.current) {
+        return;
+    }
+    curriculum.applied = Some(curriculum.current);
+    // Fitnesses from another stage are not comparable
+    if let Some(mut cache) = cache {
+        cache.clear();
+    }
+
+    // Settings carry over from earlier stages, which may not have been applied
+    // when resuming a session
+    let (mut stage_environment, mut test_time, mut weights) = (None, None, None);
+    for stage in curriculum.stages.iter().take(curriculum.current + 1) {
+        stage_environment = stage.environment.as_ref().or(stage_environment);
+        test_time = stage.test_time.or(test_time);
+        weights = stage.fitness_weights.as_ref().or(weights);
+    }
+
+    if let Some(stage_environment) = stage_environment {
+        if *environment != *stage_environment {
+            *environment = stage_environment.clone();
+            write_environment(&gen_test_conf.session, &environment);
+        }
+    }
+    if let Some(test_time) = test_time {
+        gen_test_conf.test_time = test_time;
+    }
+    if let Some(weights) = weights {
+        generation.fitness_weights = weights.clone();
+    }
+}
@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+use bevy_rapier3d::{
+    dynamics::{ImpulseJoint, Velocity},
+    geometry::ColliderMassProperties,
+    plugin::RapierContext,
+};
+use creature_builder::limb::{box_mass, CreatureLimb};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    fitness::{EvolutionFitnessEval, PENALTY_FITNESS},
+    generation::EvolutionGeneration,
+    populate::GenerationPopulator,
+    GroundMarker,
+};
+
+
+/// The worst signs of a creature exploiting the physics seen during its tests
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PhysicsDiagnostics {
+    /// The furthest apart the two anchors of any joint were
+    pub joint_separation: f32,
+    /// The deepest any limb sank into the ground
+    pub ground_penetration: f32,
+    /// The largest gain in kinetic energy per unit of mass over one step
+    pub energy_gain: f32,
+}
+
+impl PhysicsDiagnostics {
+    /// The worst of each measurement of both
+    pub fn max(self, other: Self) -> Self {
+        Self {
+            joint_separation: self.joint_separation.max(other.joint_separation),
+            ground_penetration: self.ground_penetration.max(other.ground_penetration),
+            energy_gain: self.energy_gain.max(other.energy_gain),
+        }
+    }
+}
+
+
+/// What happens to creatures that break the limits of an [`ExploitLimits`]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ExploitPenalty {
+    /// The creature is given the penalty fitness, so it is never selected
+    Disqualify,
+    /// This amount is taken from the fitness for each limit broken
+    Penalize(f32),
+}
+
+
+/// Thresholds on the [`PhysicsDiagnostics`] of a creature past which it is
+/// taken to be exploiting the physics
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExploitLimits {
+    pub max_joint_separation: f32,
+    pub max_ground_penetration: f32,
+    pub max_energy_gain: f32,
+    pub penalty: ExploitPenalty,
+}
+
+impl Default for ExploitLimits {
+    fn default() -> Self {
+        Self { max_joint_separation: 0.25, max_ground_penetration: 0.1, max_energy_gain: 20.0, penalty: ExploitPenalty::Disqualify }
+    }
+}
+
+impl ExploitLimits {
+    pub fn new(penalty: ExploitPenalty) -> Self {
+        Self { penalty, ..Default::default() }
+    }
+
+    /// The number of limits the diagnostics break
+    pub fn violations(&self, diagnostics: &PhysicsDiagnostics) -> usize {
+        [
+            diagnostics.joint_separation > self.max_joint_separation,
+            diagnostics.ground_penetration > self.max_ground_penetration,
+            diagnostics.energy_gain > self.max_energy_gain,
+        ]
+        .into_iter()
+        .filter(|broken| *broken)
+        .count()
+    }
+
+    /// The fitness of a creature with the given diagnostics once penalized
+    pub fn penalize(&self, fitness: f32, diagnostics: &PhysicsDiagnostics) -> f32 {
+        match (self.violations(diagnostics), self.penalty) {
+            (0, _) => fitness,
+            (_, ExploitPenalty::Disqualify) => PENALTY_FITNESS,
+            (violations, ExploitPenalty::Penalize(amount)) => fitness - amount * violations as f32,
+        }
+    }
+}
+
+
+/// Follows the diagnostics of the creature being tested
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DiagnosticsTracker {
+    pub(crate) worst: PhysicsDiagnostics,
+    last_energy: Option<f32>,
+}
+
+impl DiagnosticsTracker {
+    /// Starts following a new test, keeping the worst measurements of earlier
+    /// trials of the same creature unless `reset`
+    pub(crate) fn restart(&mut self, reset: bool) {
+        if reset {
+            self.worst = PhysicsDiagnostics::default();
+        }
+        self.last_energy = None;
+    }
+
+    fn observe(&mut self, joint_separation: f32, ground_penetration: f32, energy: f32) {
+        let energy_gain = self.last_energy.map_or(0.0, |last| energy - last);
+        self.last_energy = Some(energy);
+        self.worst = self.worst.max(PhysicsDiagnostics { joint_separation, ground_penetration, energy_gain });
+    }
+}
+
+
+/// The mass and kinetic energy of a limb, treating it as a solid box
+fn limb_energy(transform: &Transform, velocity: &Velocity, mass: &ColliderMassProperties) -> (f32, f32) {
+    let size = transform.scale * 2.0;
+    let mass = box_mass(transform.scale, mass);
+    let inertia =
+        mass / 12.0 * Vec3::new(size.y * size.y + size.z * size.z, size.x * size.x + size.z * size.z, size.x * size.x + size.y * size.y);
+    let spin = transform.rotation.inverse() * velocity.angvel;
+    (mass, 0.5 * mass * velocity.linvel.length_squared() + 0.5 * inertia.dot(spin * spin))
+}
+
+
+/// Measures how far the joints of the creature being tested have come apart,
+/// how deep it has sunk into the ground and how suddenly it gained energy
+pub(crate) fn measure_physics<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
+    mut generation: ResMut<EvolutionGeneration<F>>,
+    rapier: Res<RapierContext>,
+    limbs: Query<(Entity, &CreatureLimb, &Transform, &Velocity, &ColliderMassProperties)>,
+    joints: Query<(&CreatureLimb, &Transform, &ImpulseJoint)>,
+    transforms: Query<&Transform>,
+    ground: Query<(), With<GroundMarker>>,
+) {
+    let Some(creature) = generation.current_creature else { return };
+    if generation.waiting_for_fall {
+        return;
+    }
+
+    let mut joint_separation: f32 = 0.0;
+    for (_, transform, joint) in joints.iter().filter(|(limb, _, _)| limb.creature == creature) {
+        let Ok(parent) = transforms.get(joint.parent) else { continue };
+        let anchor1 = parent.translation + parent.rotation * joint.data.local_anchor1();
+        let anchor2 = transform.translation + transform.rotation * joint.data.local_anchor2();
+        joint_separation = joint_separation.max(anchor1.distance(anchor2));
+    }
+
+    let (mut ground_penetration, mut total_mass, mut total_energy): (f32, f32, f32) = (0.0, 0.0, 0.0);
+    for (entity, _, transform, velocity, mass) in limbs.iter().filter(|(_, limb, _, _, _)| limb.creature == creature) {
+        let (mass, energy) = limb_energy(transform, velocity, mass);
+        total_mass += mass;
+        total_energy += energy;
+
+        for pair in rapier.contact_pairs_with(entity) {
+            if !ground.contains(pair.collider1()) && !ground.contains(pair.collider2()) {
+                continue;
+            }
+            for manifold in pair.manifolds() {
+                for point in manifold.points() {
+                    ground_penetration = ground_penetration.max(-point.dist());
+                }
+            }
+        }
+    }
+
+    generation.current_diagnostics.observe(joint_separation, ground_penetration, total_energy / total_mass.max(f32::EPSILON));
+}
+
+
+/// Penalizes the fitness and objectives of every tested creature that broke the
+/// limits of the populator, before the generation is written
+pub(crate) fn penalize_exploits<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
+    mut generation: ResMut<EvolutionGeneration<F>>,
+    populator: Res<GenerationPopulator>,
+) {
+    let Some(limits) = &populator.exploit_limits else { return };
+    let generation = &mut *generation;
+    for (i, diagnostics) in generation.diagnostics.iter().enumerate() {
+        if let Some(fitness) = generation.fitnesses.get_mut(i) {
+            *fitness = limits.penalize(*fitness, diagnostics);
+        }
+        if let Some(objectives) = generation.objectives.get_mut(i) {
+            objectives.iter_mut().for_each(|objective| *objective = limits.penalize(*objective, diagnostics));
+        }
+    }
+}
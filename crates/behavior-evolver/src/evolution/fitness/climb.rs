@@ -0,0 +1,48 @@
+use super::{EvolutionFitnessEval, FitnessEvalInput, PENALTY_FITNESS};
+
+/// Rewards raising the lowest point of the creature and staying up there, as
+/// when climbing stairs. Only the lowest height held over the last
+/// `1 / SUSTAIN_PORTION` of the test counts, so a single jump scores nothing,
+/// and no height is gained unless the creature ends the test touching the
+/// terrain, so it can't score by being airborne at the end
+#[derive(Default)]
+pub struct ClimbFitnessEval {
+    start_height: Option<f32>,
+    heights: Vec<f32>,
+}
+
+
+impl ClimbFitnessEval {
+    const SUSTAIN_PORTION: usize = 4;
+
+    /// The lowest height the lowest point held over the end of the test
+    fn sustained_height(&self) -> Option<f32> {
+        let window = (self.heights.len() / Self::SUSTAIN_PORTION).max(1);
+        self.heights.iter().rev().take(window).copied().reduce(f32::min)
+    }
+}
+
+
+impl EvolutionFitnessEval for ClimbFitnessEval {
+    fn eval_start(&mut self, input: FitnessEvalInput) {
+        self.start_height = Some(input.lowest_point());
+    }
+
+    fn eval_continuous(&mut self, input: FitnessEvalInput) {
+        if self.start_height.is_none() {
+            self.start_height = Some(input.lowest_point());
+        }
+        self.heights.push(input.lowest_point());
+    }
+
+    fn final_eval(&self, input: FitnessEvalInput) -> f32 {
+        let start = self.start_height.unwrap_or(0.0);
+        let gain = self.sustained_height().unwrap_or(start) - start;
+        let res = if input.ground_contact_ratio() > 0.0 { gain } else { gain.min(0.0) };
+        if res.is_finite() {
+            res
+        } else {
+            PENALTY_FITNESS
+        }
+    }
+}
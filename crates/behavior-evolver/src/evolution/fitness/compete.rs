@@ -0,0 +1,63 @@
+use bevy::math::Vec3;
+
+use super::{EvolutionFitnessEval, FitnessEvalInput};
+
+/// Scores one side of a match for control of the object between two creatures.
+/// The positions of the object and the opponent are observed before each
+/// evaluation
+pub struct CompetitionFitnessEval {
+    object: Vec3,
+    opponent: Vec3,
+    control_steps: usize,
+    steps: usize,
+    /// Ordered: [control, final margin]
+    weights: [f32; 2],
+}
+
+
+impl CompetitionFitnessEval {
+    /// Sets the position of the object and the opponent's center of mass for
+    /// the next evaluation
+    pub fn observe(&mut self, object: Vec3, opponent: Vec3) {
+        self.object = object;
+        self.opponent = opponent;
+    }
+}
+
+
+impl EvolutionFitnessEval for CompetitionFitnessEval {
+    fn eval_start(&mut self, _input: FitnessEvalInput) {
+        self.control_steps = 0;
+        self.steps = 0;
+    }
+
+    fn eval_continuous(&mut self, input: FitnessEvalInput) {
+        self.steps += 1;
+        if input.center_of_mass().distance(self.object) < self.opponent.distance(self.object) {
+            self.control_steps += 1;
+        }
+    }
+
+    /// The unweighted terms of the fitness.
+    /// Ordered: [portion of the match spent closer to the object than the
+    /// opponent, how much closer to the object than the opponent at the end]
+    fn terms(&self, input: &FitnessEvalInput) -> Vec<f32> {
+        let own = input.center_of_mass().distance(self.object);
+        let opponent = self.opponent.distance(self.object);
+        vec![self.control_steps as f32 / self.steps.max(1) as f32, opponent - own]
+    }
+
+    fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    fn weights_mut(&mut self) -> &mut [f32] {
+        &mut self.weights
+    }
+}
+
+impl Default for CompetitionFitnessEval {
+    fn default() -> Self {
+        Self { object: Vec3::ZERO, opponent: Vec3::ZERO, control_steps: 0, steps: 0, weights: [1.0; 2] }
+    }
+}
@@ -0,0 +1,160 @@
+use bevy::math::{Quat, Vec2, Vec3, Vec3Swizzles};
+use serde::{Deserialize, Serialize};
+
+use super::{EvolutionFitnessEval, FitnessEvalInput, PENALTY_FITNESS};
+
+
+/// A measurement of a test that a [`CompositeFitnessEval`] can weigh
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FitnessTerm {
+    /// The greatest height the lowest point of the creature reached
+    MaxHeight,
+    /// How far the center of mass moved along the ground
+    Displacement,
+    /// How far the center of mass moved along the heading, +x unless set
+    DirectionalDisplacement,
+    /// The mean speed of the center of mass along the ground
+    AverageSpeed,
+    /// The mean cosine of how far each limb tilted from its starting
+    /// orientation, 1 for a creature that never tilts
+    Uprightness,
+    /// The work done by the creature's effectors
+    EnergyUse,
+    /// The mean portion of the limbs touching the ground
+    GroundContact,
+}
+
+impl FitnessTerm {
+    /// Every term, in the order of the weights of a [`CompositeFitnessEval`]
+    pub const ALL: [Self; 7] = [
+        Self::MaxHeight,
+        Self::Displacement,
+        Self::DirectionalDisplacement,
+        Self::AverageSpeed,
+        Self::Uprightness,
+        Self::EnergyUse,
+        Self::GroundContact,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "height" => Some(Self::MaxHeight),
+            "displacement" => Some(Self::Displacement),
+            "forward" => Some(Self::DirectionalDisplacement),
+            "speed" => Some(Self::AverageSpeed),
+            "upright" => Some(Self::Uprightness),
+            "energy" => Some(Self::EnergyUse),
+            "contact" => Some(Self::GroundContact),
+            _ => None,
+        }
+    }
+
+    fn index(&self) -> usize {
+        Self::ALL.iter().position(|term| term == self).unwrap()
+    }
+}
+
+
+/// A fitness blended at runtime from a weighted sum of [`FitnessTerm`]s, so new
+/// fitnesses need no new evaluator. The weights are set through
+/// `set_weights` in the order of `FitnessTerm::ALL`, and only measure distance
+/// moved along the ground by default
+pub struct CompositeFitnessEval {
+    heading: Vec2,
+    start: Option<(Vec3, Vec<Quat>)>,
+    max_height: f32,
+    speed_sum: f32,
+    upright_sum: f32,
+    contact_sum: f32,
+    steps: usize,
+    weights: [f32; 7],
+}
+
+
+impl CompositeFitnessEval {
+    /// The weights of a blend of the given terms, leaving out every other term
+    pub fn blend(terms: &[(FitnessTerm, f32)]) -> Vec<f32> {
+        let mut weights = vec![0.0; FitnessTerm::ALL.len()];
+        terms.iter().for_each(|(term, weight)| weights[term.index()] = *weight);
+        weights
+    }
+}
+
+
+impl EvolutionFitnessEval for CompositeFitnessEval {
+    fn eval_start(&mut self, input: FitnessEvalInput) {
+        let rotations = input.limbs.iter().map(|(transform, _)| transform.rotation).collect();
+        self.start = Some((input.center_of_mass(), rotations));
+    }
+
+    fn eval_continuous(&mut self, input: FitnessEvalInput) {
+        if self.start.is_none() {
+            self.eval_start(input.clone());
+        }
+        let Some((_, rotations)) = &self.start else { return };
+
+        self.steps += 1;
+        self.max_height = self.max_height.max(input.lowest_point());
+
+        self.speed_sum += input.center_velocity().xz().length();
+
+        let tilts: Vec<f32> =
+            input.limbs.iter().zip(rotations).map(|((transform, _), start)| (transform.rotation * start.inverse() * Vec3::Y).y).collect();
+        self.upright_sum += tilts.iter().sum::<f32>() / tilts.len().max(1) as f32;
+        self.contact_sum += input.ground_contact_ratio();
+    }
+
+    /// The weighted terms that have a weight, so that terms weighted
+    /// negatively are minimized
+    fn final_objectives(&self, input: FitnessEvalInput) -> Vec<f32> {
+        self.terms(&input)
+            .iter()
+            .zip(self.weights.iter())
+            .filter(|(_, weight)| **weight != 0.0)
+            .map(|(term, weight)| if term.is_finite() { term * weight } else { PENALTY_FITNESS })
+            .collect()
+    }
+
+    /// The unweighted terms of the fitness, ordered like `FitnessTerm::ALL`
+    fn terms(&self, input: &FitnessEvalInput) -> Vec<f32> {
+        let start = self.start.as_ref().map_or(Vec3::ZERO, |(center, _)| *center);
+        let moved: Vec2 = (input.center_of_mass() - start).xz();
+        let steps = self.steps.max(1) as f32;
+        vec![
+            self.max_height,
+            moved.length(),
+            moved.dot(self.heading),
+            self.speed_sum / steps,
+            self.upright_sum / steps,
+            input.energy.work,
+            self.contact_sum / steps,
+        ]
+    }
+
+    fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    fn weights_mut(&mut self) -> &mut [f32] {
+        &mut self.weights
+    }
+
+    fn set_heading(&mut self, heading: Vec2) {
+        self.heading = heading.try_normalize().unwrap_or(Vec2::X);
+    }
+}
+
+impl Default for CompositeFitnessEval {
+    fn default() -> Self {
+        Self {
+            heading: Vec2::X,
+            start: None,
+            max_height: -1.0,
+            speed_sum: 0.0,
+            upright_sum: 0.0,
+            contact_sum: 0.0,
+            steps: 0,
+            weights: [0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+        }
+    }
+}
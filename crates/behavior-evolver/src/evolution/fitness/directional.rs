@@ -0,0 +1,58 @@
+use bevy::math::{Quat, Vec2, Vec3, Vec3Swizzles};
+
+use super::{EvolutionFitnessEval, FitnessEvalInput};
+
+/// Rewards walking in a straight line along a heading, penalizing drifting to
+/// either side of it and turning the root limb away from where it started
+/// facing
+pub struct DirectionalWalkFitnessEval {
+    heading: Vec2,
+    init_pos: Vec2,
+    init_rotation: Quat,
+    /// Ordered: [distance along heading, drift penalty, turning penalty]
+    weights: [f32; 3],
+}
+
+
+impl EvolutionFitnessEval for DirectionalWalkFitnessEval {
+    fn eval_start(&mut self, input: FitnessEvalInput) {
+        self.init_pos = input.center_of_mass().xz();
+        self.init_rotation = input.limbs.get(input.root).map_or(Quat::IDENTITY, |(transform, _)| transform.rotation);
+    }
+
+    fn eval_continuous(&mut self, _input: FitnessEvalInput) {}
+
+    /// The unweighted terms of the fitness.
+    /// Ordered: [distance along heading, drift penalty, turning penalty]
+    fn terms(&self, input: &FitnessEvalInput) -> Vec<f32> {
+        let moved = input.center_of_mass().xz() - self.init_pos;
+        let forward = moved.dot(self.heading);
+        let drift = moved.perp_dot(self.heading).abs();
+
+        // How far the root turned about the vertical, in radians. A root pitched
+        // to face straight up or down has no facing along the ground
+        let rotation = input.limbs.get(input.root).map_or(Quat::IDENTITY, |(transform, _)| transform.rotation);
+        let facing = (rotation * self.init_rotation.inverse() * Vec3::new(self.heading.x, 0.0, self.heading.y)).xz();
+        let turn = facing.try_normalize().map_or(0.0, |facing| self.heading.angle_between(facing).abs());
+
+        vec![forward, -drift, -turn]
+    }
+
+    fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    fn weights_mut(&mut self) -> &mut [f32] {
+        &mut self.weights
+    }
+
+    fn set_heading(&mut self, heading: Vec2) {
+        self.heading = heading.try_normalize().unwrap_or(Vec2::X);
+    }
+}
+
+impl Default for DirectionalWalkFitnessEval {
+    fn default() -> Self {
+        Self { heading: Vec2::X, init_pos: Vec2::ZERO, init_rotation: Quat::IDENTITY, weights: [1.0; 3] }
+    }
+}
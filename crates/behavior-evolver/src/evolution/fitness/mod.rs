@@ -1,18 +1,173 @@
+pub mod climb;
+pub mod compete;
+pub mod composite;
+pub mod directional;
 pub mod jump;
+pub mod push;
+pub mod swim;
 pub mod walk;
 
-use bevy::transform::components::Transform;
+use bevy::{
+    math::{Vec2, Vec3},
+    transform::components::Transform,
+};
 use bevy_rapier3d::dynamics::Velocity;
+use creature_builder::{effector::CreatureEnergy, joint::CreatureJointId, sensor::LimbCollisionType};
 
 
+/// The fitness given to creatures whose test broke down
+pub const PENALTY_FITNESS: f32 = -1000000000000.0;
+
+
+/// The state of one joint of the creature being tested
+#[derive(Clone, Debug)]
+pub struct JointEvalInput {
+    pub id: CreatureJointId,
+    /// The angle between the child limb and its parent about each axis of the
+    /// child
+    pub angles: Vec3,
+    /// The clamped force the effectors applied along each axis in the last
+    /// step.
+    /// Ordered: [X, Y, Z, AngX, AngY, AngZ]
+    pub outputs: [f32; 6],
+}
+
+
+/// The block the creature is being tested pushing
+#[derive(Clone, Debug)]
+pub struct BlockEvalInput {
+    pub transform: Transform,
+    /// Where the block should be pushed to
+    pub goal: Vec3,
+}
+
+
+#[derive(Clone, Default)]
 pub struct FitnessEvalInput {
     pub limbs: Vec<(Transform, Velocity)>,
+    /// The index in `limbs` of the root limb
+    pub root: usize,
+    /// What each face of each limb is touching, in the same order as `limbs`
+    pub contacts: Vec<[LimbCollisionType; 6]>,
+    /// The mass of each limb, in the same order as `limbs`
+    pub masses: Vec<f32>,
+    pub joints: Vec<JointEvalInput>,
+    /// The block to push, if the creature is tested with one
+    pub block: Option<BlockEvalInput>,
+    /// The energy the creature has spent actuating its joints so far
+    pub energy: CreatureEnergy,
+    /// The number of steps the creature has been tested for so far
+    pub step: usize,
     pub test_time: usize,
 }
 
+impl FitnessEvalInput {
+    /// The portion of the limbs that are touching the ground
+    pub fn ground_contact_ratio(&self) -> f32 {
+        let touching = self.contacts.iter().filter(|faces| faces.contains(&LimbCollisionType::GroundCollision)).count();
+        touching as f32 / self.contacts.len().max(1) as f32
+    }
+
+    /// The mass of the limb at `index`, or its volume if masses are unknown
+    fn limb_mass(&self, index: usize) -> f32 {
+        let scale = self.limbs[index].0.scale;
+        self.masses.get(index).copied().unwrap_or(scale.x * scale.y * scale.z)
+    }
+
+    /// The center of mass of the creature
+    pub fn center_of_mass(&self) -> Vec3 {
+        let (mut total_pos, mut total_mass) = (Vec3::ZERO, 0.0);
+        for (i, (transform, _)) in self.limbs.iter().enumerate() {
+            total_mass += self.limb_mass(i);
+            total_pos += transform.translation * self.limb_mass(i);
+        }
+        total_pos / if total_mass != 0.0 { total_mass } else { 1.0 }
+    }
+
+    /// The velocity of the center of mass of the creature
+    pub fn center_velocity(&self) -> Vec3 {
+        let (mut total_vel, mut total_mass) = (Vec3::ZERO, 0.0);
+        for (i, (_, velocity)) in self.limbs.iter().enumerate() {
+            total_mass += self.limb_mass(i);
+            total_vel += velocity.linvel * self.limb_mass(i);
+        }
+        total_vel / if total_mass != 0.0 { total_mass } else { 1.0 }
+    }
+
+    /// The height of the lowest corner of any limb
+    pub fn lowest_point(&self) -> f32 {
+        let mut lowest = f32::MAX;
+        for (transform, _) in self.limbs.iter() {
+            let extent = (transform.local_x() * transform.scale.x).y.abs()
+                + (transform.local_y() * transform.scale.y).y.abs()
+                + (transform.local_z() * transform.scale.z).y.abs();
+            lowest = lowest.min(transform.translation.y - extent);
+        }
+        lowest
+    }
+}
+
 
 pub trait EvolutionFitnessEval {
     fn eval_start(&mut self, input: FitnessEvalInput);
     fn eval_continuous(&mut self, input: FitnessEvalInput);
-    fn final_eval(&self, input: FitnessEvalInput) -> f32;
+    /// The unweighted terms of a fitness made of weighted terms, in the order of
+    /// `weights`. Evaluators without weighted terms have none
+    fn terms(&self, _input: &FitnessEvalInput) -> Vec<f32> {
+        Vec::new()
+    }
+    /// The weight of each term
+    fn weights(&self) -> &[f32] {
+        &[]
+    }
+    fn weights_mut(&mut self) -> &mut [f32] {
+        &mut []
+    }
+    /// The weighted sum of the terms, or the penalty fitness if it isn't finite
+    fn final_eval(&self, input: FitnessEvalInput) -> f32 {
+        let res: f32 = self.terms(&input).iter().zip(self.weights()).map(|(term, weight)| term * weight).sum();
+        if res.is_finite() {
+            res
+        } else {
+            PENALTY_FITNESS
+        }
+    }
+    /// The separate objectives that make up the fitness, each of which is
+    /// maximized by multi-objective selection. These are the terms, or the
+    /// fitness itself for evaluators without weighted terms
+    fn final_objectives(&self, input: FitnessEvalInput) -> Vec<f32> {
+        let terms = self.terms(&input);
+        if terms.is_empty() {
+            return vec![self.final_eval(input)];
+        }
+        terms.iter().map(|term| if term.is_finite() { *term } else { PENALTY_FITNESS }).collect()
+    }
+    /// Sets the weights of the terms that make up the fitness, as scheduled by
+    /// a curriculum. Evaluators without weighted terms ignore them
+    fn set_weights(&mut self, weights: &[f32]) {
+        self.weights_mut().iter_mut().zip(weights).for_each(|(weight, new)| *weight = *new);
+    }
+    /// Sets the direction along the ground, as (x, z), that directional
+    /// evaluators reward moving in. Other evaluators ignore it
+    fn set_heading(&mut self, _heading: Vec2) {}
+    /// Checked at every step of a test, returning a fitness to end the test
+    /// early with, such as for a creature that can no longer score well
+    fn should_terminate(&self, _input: &FitnessEvalInput) -> Option<f32> {
+        None
+    }
+}
+
+
+/// The penalty fitness if any limb has a non-finite transform or velocity, or
+/// is moving or spinning faster than `max_speed`, as when the physics blow up
+pub fn broken_limbs(input: &FitnessEvalInput, max_speed: f32) -> Option<f32> {
+    let broken = input.limbs.iter().any(|(transform, velocity)| {
+        !transform.translation.is_finite()
+            || !transform.rotation.is_finite()
+            || !velocity.linvel.is_finite()
+            || !velocity.angvel.is_finite()
+            || velocity.linvel.length() > max_speed
+            || velocity.angvel.length() > max_speed
+    });
+    broken.then_some(PENALTY_FITNESS)
 }
@@ -0,0 +1,64 @@
+use bevy::math::{Vec2, Vec3Swizzles};
+
+use super::{EvolutionFitnessEval, FitnessEvalInput};
+
+/// Rewards pushing the block of a `PushTask` towards its goal. Scores nothing
+/// when creatures are tested without a block
+pub struct PushFitnessEval {
+    /// The distance along the ground from the block to its goal at the start
+    start_distance: Option<f32>,
+    closest_approach: f32,
+    /// Ordered: [progress towards the goal, approach penalty]
+    weights: [f32; 2],
+}
+
+
+impl PushFitnessEval {
+    fn goal_distance(input: &FitnessEvalInput) -> Option<f32> {
+        input.block.as_ref().map(|block| (block.goal.xz() - block.transform.translation.xz()).length())
+    }
+}
+
+
+impl EvolutionFitnessEval for PushFitnessEval {
+    fn eval_start(&mut self, input: FitnessEvalInput) {
+        self.start_distance = Self::goal_distance(&input);
+    }
+
+    fn eval_continuous(&mut self, input: FitnessEvalInput) {
+        if self.start_distance.is_none() {
+            self.start_distance = Self::goal_distance(&input);
+        }
+        let Some(block) = &input.block else { return };
+
+        let center: Vec2 = input.center_of_mass().xz();
+        self.closest_approach = self.closest_approach.min(center.distance(block.transform.translation.xz()));
+    }
+
+    /// The unweighted terms of the fitness.
+    /// Ordered: [how much closer to the goal the block ends up, how far the
+    /// creature stayed from the block at its closest, which guides creatures
+    /// that haven't reached the block yet]
+    fn terms(&self, input: &FitnessEvalInput) -> Vec<f32> {
+        let progress = match (self.start_distance, Self::goal_distance(input)) {
+            (Some(start), Some(end)) => start - end,
+            _ => 0.0,
+        };
+        let approach = if self.closest_approach == f32::MAX { 0.0 } else { -self.closest_approach };
+        vec![progress, approach]
+    }
+
+    fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    fn weights_mut(&mut self) -> &mut [f32] {
+        &mut self.weights
+    }
+}
+
+impl Default for PushFitnessEval {
+    fn default() -> Self {
+        Self { start_distance: None, closest_approach: f32::MAX, weights: [1.0, 0.1] }
+    }
+}
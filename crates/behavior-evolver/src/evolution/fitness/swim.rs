@@ -0,0 +1,33 @@
+use bevy::math::Vec3;
+
+use super::{EvolutionFitnessEval, FitnessEvalInput, PENALTY_FITNESS};
+
+/// Rewards moving the center of mass as far as possible in any direction while
+/// in water
+pub struct SwimFitnessEval {
+    init_pos: Vec3,
+}
+
+
+impl EvolutionFitnessEval for SwimFitnessEval {
+    fn eval_start(&mut self, input: FitnessEvalInput) {
+        self.init_pos = input.center_of_mass();
+    }
+
+    fn eval_continuous(&mut self, _input: FitnessEvalInput) {}
+
+    fn final_eval(&self, input: FitnessEvalInput) -> f32 {
+        let res = (input.center_of_mass() - self.init_pos).length();
+        if res.is_finite() {
+            res
+        } else {
+            PENALTY_FITNESS
+        }
+    }
+}
+
+impl Default for SwimFitnessEval {
+    fn default() -> Self {
+        Self { init_pos: Vec3::ZERO }
+    }
+}
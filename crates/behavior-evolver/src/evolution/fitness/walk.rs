@@ -6,6 +6,8 @@ pub struct WalkFitnessEval {
     max_height: f32,
     init_pos: Vec2,
     init_length: f32,
+    /// Ordered: [distance, height penalty, length change penalty]
+    weights: [f32; 3],
 }
 
 
@@ -64,7 +66,9 @@ impl EvolutionFitnessEval for WalkFitnessEval {
         // let new_pos = total_pos / if count != 0.0 { count } else { 1.0 };
     }
 
-    fn final_eval(&self, input: FitnessEvalInput) -> f32 {
+    /// The unweighted terms of the fitness.
+    /// Ordered: [distance, height penalty, length change penalty]
+    fn terms(&self, input: &FitnessEvalInput) -> Vec<f32> {
         let mut max_point = Vec3::splat(f32::MIN);
         let mut min_point = Vec3::splat(f32::MAX);
         input.limbs.iter().for_each(|(transform, _)| {
@@ -104,19 +108,24 @@ impl EvolutionFitnessEval for WalkFitnessEval {
         });
         let end_pos = total_pos / if count != 0.0 { count } else { 1.0 };
 
-        let res = (end_pos - self.init_pos).length()
-            - if self.max_height > 2.0 { self.max_height * self.max_height * 5.0 } else { 0.0 }
-            - if length_diff > 1.0 { length_diff * length_diff } else { 0.0 };
-        if res.is_finite() {
-            res
-        } else {
-            -1000000000000.0
-        }
+        vec![
+            (end_pos - self.init_pos).length(),
+            if self.max_height > 2.0 { -self.max_height * self.max_height * 5.0 } else { 0.0 },
+            if length_diff > 1.0 { -length_diff * length_diff } else { 0.0 },
+        ]
+    }
+
+    fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+
+    fn weights_mut(&mut self) -> &mut [f32] {
+        &mut self.weights
     }
 }
 
 impl Default for WalkFitnessEval {
     fn default() -> Self {
-        Self { max_height: -1.0, init_length: 0.0, init_pos: Vec2::ZERO }
+        Self { max_height: -1.0, init_length: 0.0, init_pos: Vec2::ZERO, weights: [1.0; 3] }
     }
 }
@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use bevy_rapier3d::{
+    dynamics::{ExternalImpulse, Velocity},
+    plugin::{RapierConfiguration, TimestepMode},
+};
+use creature_builder::limb::CreatureLimb;
+use serde::{Deserialize, Serialize};
+
+
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FluidConfig {
+    /// The drag force applied per unit of face area per unit of velocity
+    pub viscosity: f32,
+}
+
+impl FluidConfig {
+    /// The drag force and torque on a limb moving through the fluid, summed
+    /// over every face that opposes the velocity of the face along its normal,
+    /// proportional to the area of the face
+    pub fn drag(&self, transform: &Transform, velocity: &Velocity) -> (Vec3, Vec3) {
+        let half_size = transform.scale;
+        let faces = [
+            (transform.local_x(), half_size.x, 4.0 * half_size.y * half_size.z),
+            (transform.local_y(), half_size.y, 4.0 * half_size.x * half_size.z),
+            (transform.local_z(), half_size.z, 4.0 * half_size.x * half_size.y),
+        ];
+
+        let (mut force, mut torque) = (Vec3::ZERO, Vec3::ZERO);
+        for (axis, offset, area) in faces {
+            for normal in [axis, -axis] {
+                let r = normal * offset;
+                let face_velocity = velocity.linvel + velocity.angvel.cross(r);
+                let normal_velocity = face_velocity.dot(normal);
+                // Only faces pushing into the fluid are resisted
+                if normal_velocity <= 0.0 {
+                    continue;
+                }
+
+                let face_force = -normal * normal_velocity * area * self.viscosity;
+                force += face_force;
+                torque += r.cross(face_force);
+            }
+        }
+        (force, torque)
+    }
+}
+
+impl Default for FluidConfig {
+    fn default() -> Self {
+        Self { viscosity: 1.0 }
+    }
+}
+
+
+/// Applies the drag of the fluid to every limb
+pub(crate) fn fluid_drag(
+    mut limbs: Query<(&Transform, &Velocity, &mut ExternalImpulse), With<CreatureLimb>>,
+    fluid: Res<FluidConfig>,
+    rapier: Res<RapierConfiguration>,
+    time: Res<Time>,
+) {
+    // The impulse is applied once per physics step
+    let dt = match rapier.timestep_mode {
+        TimestepMode::Fixed { dt, .. } | TimestepMode::Interpolated { dt, .. } => dt,
+        TimestepMode::Variable { max_dt, .. } => time.delta_seconds().min(max_dt),
+    };
+
+    for (transform, velocity, mut impulse) in limbs.iter_mut() {
+        let (force, torque) = fluid.drag(transform, velocity);
+        impulse.impulse += force * dt;
+        impulse.torque_impulse += torque * dt;
+    }
+}
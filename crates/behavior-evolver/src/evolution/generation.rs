@@ -1,17 +1,33 @@
 use std::collections::HashMap;
 
-use bevy::prelude::*;
+use bevy::{ecs::system::SystemParam, prelude::*};
 use bevy_rapier3d::{
-    dynamics::Velocity,
-    geometry::{Friction, Restitution},
+    dynamics::{ImpulseJoint, Velocity},
+    geometry::{ColliderMassProperties, Friction, Restitution},
+};
+use creature_builder::{
+    builder::node::{BuildResult, CreatureMorphologyGraph},
+    config::CreatureBuilderConfig,
+    effector::{CreatureEnergy, CreatureJointEffectors, JointContext},
+    joint::CreatureJoint,
+    limb::{box_mass, CreatureLimb},
+    sensor::LimbCollisionSensor,
+    CreatureId,
 };
-use creature_builder::{builder::node::CreatureMorphologyGraph, config::CreatureBuilderConfig, limb::CreatureLimb, CreatureId};
 
 use super::{
-    fitness::{EvolutionFitnessEval, FitnessEvalInput},
+    cache::{morphology_hash, FitnessCache},
+    diagnostics::{DiagnosticsTracker, PhysicsDiagnostics},
+    fitness::{broken_limbs, BlockEvalInput, EvolutionFitnessEval, FitnessEvalInput, JointEvalInput},
+    map_elites::CreatureTraits,
+    novelty::{BehaviorDescriptor, BehaviorTracker},
     populate::CreaturePopulateFlag,
+    push::{PushBlock, PushTask},
     state::{EvolutionState, EvolutionTrainingEvent},
-    GroundMarker,
+    terrain::Terrain,
+    trajectory::TrajectoryRecorder,
+    trials::{TrialAggregation, TrialPerturbation},
+    EnvironmentConfig, GroundMarker,
 };
 
 
@@ -22,11 +38,32 @@ pub struct GenerationTestingConfig {
     pub session: String,
     pub wait_for_fall: bool,
     pub wait_for_fall_timeout: usize,
+    /// The number of times each creature is tested, each time spawned with a
+    /// new perturbation. Only used when creatures are tested one at a time
+    pub trials: usize,
+    pub perturbation: TrialPerturbation,
+    /// How the scores of the trials are combined into the fitness
+    pub aggregation: TrialAggregation,
+    /// The linear or angular speed of a limb past which the physics of the
+    /// creature are taken to have blown up, ending its test with a penalty
+    pub blow_up_speed: f32,
+    /// The block each creature is given to push, if any
+    pub push: Option<PushTask>,
 }
 
 impl Default for GenerationTestingConfig {
     fn default() -> Self {
-        Self { test_time: 180, session: String::from("default-session"), wait_for_fall: false, wait_for_fall_timeout: 300 }
+        Self {
+            test_time: 180,
+            session: String::from("default-session"),
+            wait_for_fall: false,
+            wait_for_fall_timeout: 300,
+            trials: 1,
+            perturbation: TrialPerturbation::default(),
+            aggregation: TrialAggregation::default(),
+            blow_up_speed: 500.0,
+            push: None,
+        }
     }
 }
 
@@ -35,289 +72,453 @@ impl Default for GenerationTestingConfig {
 pub struct EvolutionGeneration<F: EvolutionFitnessEval + Send + Sync + Default + 'static> {
     pub(crate) population: Vec<CreatureMorphologyGraph>,
     pub(crate) fitnesses: Vec<f32>,
+    /// The objectives of each creature, used for multi-objective selection
+    pub(crate) objectives: Vec<Vec<f32>>,
+    /// The behavior of each creature, used for novelty search
+    pub(crate) behaviors: Vec<BehaviorDescriptor>,
+    /// The traits of each creature, used to place it in the MAP-Elites grid
+    pub(crate) traits: Vec<CreatureTraits>,
+    /// The species of each creature, assigned when the generation is populated
+    pub(crate) species: Vec<usize>,
+    /// The island of each creature, when training with an island model
+    pub(crate) islands: Vec<usize>,
+    /// The score of each trial of each creature, empty for creatures whose
+    /// fitness was cached
+    pub(crate) trial_fitnesses: Vec<Vec<f32>>,
+    /// The worst signs of exploiting the physics seen in the tests of each
+    /// creature
+    pub(crate) diagnostics: Vec<PhysicsDiagnostics>,
     pub(crate) populate_flags: Vec<CreaturePopulateFlag>,
     pub(crate) current_test: Option<usize>,
     pub(crate) current_fitness: Option<F>,
+    /// The fitness of the current test if it was ended early
+    pub(crate) terminated_fitness: Option<f32>,
+    /// The fitness and objectives of each finished trial of the current creature
+    pub(crate) current_trials: Vec<(f32, Vec<f32>)>,
+    /// The terrain of the environment before it was reseeded for a trial
+    pub(crate) base_terrain: Option<Terrain>,
+    pub(crate) current_behavior: BehaviorTracker,
+    pub(crate) current_diagnostics: DiagnosticsTracker,
     pub(crate) current_train_time: usize,
     pub(crate) current_creature: Option<CreatureId>,
+    /// The block the current creature is pushing
+    pub(crate) current_block: Option<Entity>,
     pub(crate) current_generation: usize,
     pub(crate) waiting_for_fall: bool,
     pub(crate) fall_wait_time: usize,
     pub(crate) fall_start_counter: usize,
+    /// The weights given to each new fitness evaluator
+    pub(crate) fitness_weights: Vec<f32>,
+    /// The heading given to each new fitness evaluator, if any
+    pub(crate) fitness_heading: Option<Vec2>,
 }
 
 
-pub(crate) fn test_generation_nowindow<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
-    mut commands: Commands,
-    mut generation: ResMut<EvolutionGeneration<F>>,
-    config: Res<GenerationTestingConfig>,
-    state: Res<State<EvolutionState>>,
-    mut next_state: ResMut<NextState<EvolutionState>>,
-    mut limbs: Query<(Entity, &CreatureLimb, &Transform, &Velocity, &mut Friction, &mut Restitution)>,
-    mut ground: Query<&mut Friction, (With<GroundMarker>, Without<CreatureLimb>)>,
-    mut training_evw: EventWriter<EvolutionTrainingEvent>,
-    mut build_conf: ResMut<CreatureBuilderConfig>,
-    mut limb_info_save: Local<HashMap<Entity, (f32, f32)>>,
-) {
-    match state.get() {
-        EvolutionState::EvaluatingCreature => {
-            match generation.current_test {
-                Some(i) => {
-                    let limb_pos_vels: Vec<_> = limbs
-                        .iter()
-                        .filter(|(_, limb, _, _, _, _)| limb.creature == generation.population[generation.current_test.unwrap()].creature)
-                        .map(|(_, _, pos, vel, _, _)| (*pos, *vel))
-                        .collect();
-
-                    let eval = generation
-                        .current_fitness
-                        .as_ref()
-                        .unwrap()
-                        .final_eval(FitnessEvalInput { limbs: limb_pos_vels, test_time: config.test_time });
-                    generation.fitnesses.push(eval);
-                    generation.current_fitness = Some(F::default());
-                    generation.current_test = Some(i + 1);
-                },
-                None => {
-                    generation.fitnesses.clear();
-                    generation.current_fitness = Some(F::default());
-                    generation.current_test = Some(0);
-                },
-            };
-            if generation.current_test.unwrap() < generation.population.len() {
-                if let Some(id) = generation.current_creature {
-                    limbs
-                        .iter()
-                        .filter(|(_, limb, _, _, _, _)| limb.creature == id)
-                        .for_each(|(entity, _, _, _, _, _)| commands.entity(entity).despawn());
-                }
-                let morph = &generation.population[generation.current_test.unwrap()];
-                let mut result = morph.evaluate();
-                generation.current_creature = Some(morph.creature);
-                result.align_to_ground();
-                result.build_nowindow(&mut commands);
-                if config.wait_for_fall {
-                    build_conf.behavior.disable_behavior = true;
-                    generation.waiting_for_fall = true;
-                    for (entity, _, _, _, mut friction, mut restitution) in limbs.iter_mut() {
-                        limb_info_save.insert(entity, (friction.coefficient, restitution.coefficient));
-                        friction.coefficient = 0.0;
-                        restitution.coefficient = 0.0;
-                    }
-                    for mut friction in ground.iter_mut() {
-                        friction.coefficient = 0.0;
-                    }
-                }
-                next_state.set(EvolutionState::TestingCreature);
-            } else {
-                generation.current_test = None;
-                generation.current_fitness = None;
-                next_state.set(EvolutionState::WritingGeneration);
-            }
-        },
-        EvolutionState::TestingCreature => {
-            let index = generation.current_test.unwrap();
-            let morph = &generation.population[index];
-            let creature_id = morph.creature;
-
-            let limb_pos_vels: Vec<_> = limbs
-                .iter()
-                .filter(|(_, limb, _, _, _, _)| limb.creature == creature_id)
-                .map(|(_, _, pos, vel, _, _)| (*pos, *vel))
-                .collect();
-
-            if generation.waiting_for_fall {
-                generation.fall_wait_time += 1;
-                if generation.fall_start_counter < 30 {
-                    generation.fall_start_counter += 1;
-                } else {
-                    let mut y_vel = 0.0;
-                    limb_pos_vels.iter().for_each(|x| {
-                        y_vel += x.1.linvel.y;
-                    });
-                    if y_vel.abs() < 0.01 || generation.fall_wait_time > config.wait_for_fall_timeout {
-                        generation.waiting_for_fall = false;
-                        build_conf.behavior.disable_behavior = false;
-                        generation.fall_wait_time = 0;
-                        generation.fall_start_counter = 0;
-
-                        for (entity, _, _, _, mut friction, mut restitution) in limbs.iter_mut() {
-                            let Some((f, r)) = limb_info_save.get(&entity) else { continue };
-                            friction.coefficient = *f;
-                            restitution.coefficient = *r;
-                        }
-                        for mut friction in ground.iter_mut() {
-                            friction.coefficient = 0.75;
-                        }
-                        limb_info_save.clear();
+impl<F: EvolutionFitnessEval + Send + Sync + Default + 'static> EvolutionGeneration<F> {
+    pub(crate) fn new_fitness(&self) -> F {
+        let mut fitness = F::default();
+        if !self.fitness_weights.is_empty() {
+            fitness.set_weights(&self.fitness_weights);
+        }
+        if let Some(heading) = self.fitness_heading {
+            fitness.set_heading(heading);
+        }
+        fitness
+    }
+}
 
-                        generation
-                            .current_fitness
-                            .as_mut()
-                            .unwrap()
-                            .eval_start(FitnessEvalInput { limbs: limb_pos_vels, test_time: config.test_time });
-                    }
-                }
-                return;
-            }
 
-            generation.current_train_time += 1;
+/// The results of one test of a creature
+#[derive(Clone, Debug, Default)]
+pub struct TestResult {
+    pub fitness: f32,
+    pub objectives: Vec<f32>,
+    pub behavior: BehaviorDescriptor,
+    pub traits: CreatureTraits,
+    pub diagnostics: PhysicsDiagnostics,
+}
+
+
+/// The components of a limb read into the [`FitnessEvalInput`] of its creature
+type LimbState = (
+    &'static CreatureLimb,
+    &'static Transform,
+    &'static Velocity,
+    &'static LimbCollisionSensor,
+    &'static ColliderMassProperties,
+    Has<ImpulseJoint>,
+);
+
 
-            generation
-                .current_fitness
-                .as_mut()
-                .unwrap()
-                .eval_continuous(FitnessEvalInput { limbs: limb_pos_vels, test_time: config.test_time });
+/// The parts of the world that make up the [`FitnessEvalInput`] of a creature
+#[derive(SystemParam)]
+pub(crate) struct CreatureStateQuery<'w, 's> {
+    limbs: Query<'w, 's, LimbState>,
+    joints: Query<'w, 's, (&'static CreatureJoint, &'static Transform, &'static ImpulseJoint, &'static CreatureJointEffectors)>,
+    energies: Query<'w, 's, (&'static CreatureLimb, &'static CreatureEnergy)>,
+    blocks: Query<'w, 's, (&'static Transform, &'static PushBlock)>,
+}
 
-            if generation.current_train_time > config.test_time {
-                generation.current_train_time = 0;
-                training_evw.send(EvolutionTrainingEvent::FinishedTestingCreature);
-                next_state.set(EvolutionState::EvaluatingCreature);
+impl<'w, 's> CreatureStateQuery<'w, 's> {
+    /// The state of the creature with the given id at the given step of its
+    /// test
+    pub(crate) fn input(&self, creature: CreatureId, step: usize, test_time: usize) -> FitnessEvalInput {
+        let mut input = FitnessEvalInput { step, test_time, ..Default::default() };
+        for (_, transform, velocity, sensor, mass, jointed) in self.limbs.iter().filter(|(limb, ..)| limb.creature == creature) {
+            if !jointed {
+                input.root = input.limbs.len();
             }
+            input.limbs.push((*transform, *velocity));
+            input.contacts.push(sensor.faces());
+            input.masses.push(box_mass(transform.scale, mass));
+        }
+        for (joint, transform, impulse_joint, effectors) in self.joints.iter().filter(|(joint, ..)| joint.creature == creature) {
+            let Ok((_, parent, ..)) = self.limbs.get(impulse_joint.parent) else { continue };
+            input.joints.push(JointEvalInput { id: joint.id, angles: JointContext::angles(parent, transform), outputs: effectors.outputs });
+        }
+        input.block = self
+            .blocks
+            .iter()
+            .find(|(_, block)| block.creature == creature)
+            .map(|(transform, block)| BlockEvalInput { transform: *transform, goal: block.goal });
+        input.energy = self.energies.iter().find(|(limb, _)| limb.creature == creature).map(|(_, energy)| *energy).unwrap_or_default();
+        input
+    }
+}
+
+
+/// Records the results of testing the creature at `index`, averaged with its
+/// earlier tests when caching
+fn record_test<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
+    generation: &mut EvolutionGeneration<F>,
+    cache: Option<&mut FitnessCache>,
+    index: usize,
+    test: TestResult,
+) {
+    let test = TestResult { diagnostics: generation.current_diagnostics.worst, ..test };
+    let test = match cache {
+        Some(cache) => {
+            let hash = morphology_hash(&generation.population[index]);
+            let cached = cache.record(hash, generation.current_generation, test.clone());
+            TestResult { fitness: cached.fitness, objectives: cached.objectives.clone(), ..test }
         },
+        None => test,
+    };
+    generation.fitnesses.push(test.fitness);
+    generation.objectives.push(test.objectives);
+    generation.behaviors.push(test.behavior);
+    generation.traits.push(test.traits);
+    generation.diagnostics.push(test.diagnostics);
+}
+
+
+/// Ends the test of the current creature early if its physics broke down or its
+/// fitness evaluator gave up on it, returning whether the test ended
+fn terminate_early<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
+    generation: &mut EvolutionGeneration<F>,
+    config: &GenerationTestingConfig,
+    input: &FitnessEvalInput,
+) -> bool {
+    let fitness = generation.current_fitness.as_ref().unwrap();
+    let Some(penalty) = broken_limbs(input, config.blow_up_speed).or_else(|| fitness.should_terminate(input)) else { return false };
+    generation.terminated_fitness = Some(penalty);
+    true
+}
+
 
-        _ => (),
+/// Records the results of the trial of the creature at `index` that just
+/// finished, returning whether it was the creature's last trial. The scores and
+/// objectives of all its trials are then aggregated and recorded, along with the
+/// behavior and traits of the last trial
+fn finish_trial<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
+    generation: &mut EvolutionGeneration<F>,
+    config: &GenerationTestingConfig,
+    cache: Option<&mut FitnessCache>,
+    index: usize,
+    test: TestResult,
+) -> bool {
+    generation.current_trials.push((test.fitness, test.objectives.clone()));
+    if generation.current_trials.len() < config.trials.max(1) {
+        return false;
     }
+
+    let trials = std::mem::take(&mut generation.current_trials);
+    let scores: Vec<f32> = trials.iter().map(|(fitness, _)| *fitness).collect();
+    let objectives = (0..trials[0].1.len())
+        .map(|j| {
+            let values: Vec<f32> = trials.iter().map(|(_, objectives)| objectives.get(j).copied().unwrap_or_default()).collect();
+            config.aggregation.aggregate(&values)
+        })
+        .collect();
+    record_test(generation, cache, index, TestResult { fitness: config.aggregation.aggregate(&scores), objectives, ..test });
+    generation.trial_fitnesses.push(scores);
+    true
 }
 
 
-pub(crate) fn test_generation<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-    mut generation: ResMut<EvolutionGeneration<F>>,
-    config: Res<GenerationTestingConfig>,
-    state: Res<State<EvolutionState>>,
-    mut next_state: ResMut<NextState<EvolutionState>>,
-    mut limbs: Query<(Entity, &CreatureLimb, &Transform, &Velocity, &mut Friction, &mut Restitution)>,
-    mut ground: Query<&mut Friction, (With<GroundMarker>, Without<CreatureLimb>)>,
-    mut training_evw: EventWriter<EvolutionTrainingEvent>,
-    mut build_conf: ResMut<CreatureBuilderConfig>,
-    mut limb_info_save: Local<HashMap<Entity, (f32, f32)>>,
+/// Spawns the creature of the next trial with a new perturbation, reseeding the
+/// terrain when asked to
+fn perturb_trial<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
+    generation: &EvolutionGeneration<F>,
+    config: &GenerationTestingConfig,
+    environment: &mut ResMut<EnvironmentConfig>,
+    result: &mut BuildResult,
 ) {
-    match state.get() {
-        EvolutionState::EvaluatingCreature => {
-            match generation.current_test {
-                Some(i) => {
-                    let limb_pos_vels: Vec<_> = limbs
-                        .iter()
-                        .filter(|(_, limb, _, _, _, _)| limb.creature == generation.population[generation.current_test.unwrap()].creature)
-                        .map(|(_, _, pos, vel, _, _)| (*pos, *vel))
-                        .collect();
-
-                    let eval = generation
-                        .current_fitness
-                        .as_ref()
-                        .unwrap()
-                        .final_eval(FitnessEvalInput { limbs: limb_pos_vels, test_time: config.test_time });
-                    generation.fitnesses.push(eval);
-                    generation.current_fitness = Some(F::default());
-                    generation.current_test = Some(i + 1);
-                },
-                None => {
-                    generation.fitnesses.clear();
-                    generation.current_fitness = Some(F::default());
-                    generation.current_test = Some(0);
-                },
-            };
-            if generation.current_test.unwrap() < generation.population.len() {
-                if let Some(id) = generation.current_creature {
-                    limbs
-                        .iter()
-                        .filter(|(_, limb, _, _, _, _)| limb.creature == id)
-                        .for_each(|(entity, _, _, _, _, _)| commands.entity(entity).despawn());
-                }
-                let morph = &generation.population[generation.current_test.unwrap()];
-                let mut result = morph.evaluate();
-                generation.current_creature = Some(morph.creature);
-                result.align_to_ground();
-                result.build(
-                    &mut commands,
-                    &mut meshes,
-                    &mut materials,
-                    generation.populate_flags[generation.current_test.unwrap()].into_color(),
-                );
-                if config.wait_for_fall {
-                    build_conf.behavior.disable_behavior = true;
-                    generation.waiting_for_fall = true;
-                    for (entity, _, _, _, mut friction, mut restitution) in limbs.iter_mut() {
-                        limb_info_save.insert(entity, (friction.coefficient, restitution.coefficient));
-                        friction.coefficient = 0.0;
-                        restitution.coefficient = 0.0;
+    let (offset, turn) = config.perturbation.sample(&mut rand::thread_rng());
+    if offset != Vec3::ZERO || turn != 0.0 {
+        result.nudge(offset, turn);
+    }
+    if config.perturbation.reseed_terrain {
+        let trial = generation.current_trials.len() as u64;
+        let terrain = generation.base_terrain.as_ref().map(|terrain| terrain.reseeded(trial));
+        if environment.terrain != terrain {
+            environment.terrain = terrain;
+        }
+    }
+}
+
+
+/// Carries the cached results of the next creatures to test into the
+/// generation, moving on to the first creature that has to be tested
+fn skip_cached<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
+    generation: &mut EvolutionGeneration<F>,
+    cache: Option<&FitnessCache>,
+    training_evw: &mut EventWriter<EvolutionTrainingEvent>,
+) {
+    let Some(cache) = cache else { return };
+    while let Some(i) = generation.current_test.filter(|i| *i < generation.population.len()) {
+        let Some(cached) = cache.get(morphology_hash(&generation.population[i]), generation.current_generation) else { return };
+        generation.fitnesses.push(cached.fitness);
+        generation.objectives.push(cached.objectives.clone());
+        generation.behaviors.push(cached.behavior.clone());
+        generation.traits.push(cached.traits);
+        generation.diagnostics.push(cached.diagnostics);
+        generation.trial_fitnesses.push(Vec::new());
+        generation.current_test = Some(i + 1);
+        training_evw.send(EvolutionTrainingEvent::FinishedTestingCreature);
+    }
+}
+
+
+/// The components of a limb whose contact properties are cleared while it
+/// falls into place
+type LimbContactProperties = (Entity, &'static CreatureLimb, &'static mut Friction, &'static mut Restitution);
+
+
+/// The assets creatures are built with when training with a window
+type CreatureAssets<'a, 'm, 'n> = (&'a mut ResMut<'m, Assets<Mesh>>, &'a mut ResMut<'n, Assets<StandardMaterial>>);
+
+
+/// The world a generation is tested in, shared by the windowed and windowless
+/// test systems
+#[derive(SystemParam)]
+pub(crate) struct GenerationTest<'w, 's, F: EvolutionFitnessEval + Send + Sync + Default + 'static> {
+    commands: Commands<'w, 's>,
+    generation: ResMut<'w, EvolutionGeneration<F>>,
+    config: Res<'w, GenerationTestingConfig>,
+    state: Res<'w, State<EvolutionState>>,
+    next_state: ResMut<'w, NextState<EvolutionState>>,
+    limbs: Query<'w, 's, LimbContactProperties>,
+    ground: Query<'w, 's, &'static mut Friction, (With<GroundMarker>, Without<CreatureLimb>)>,
+    creature_state: CreatureStateQuery<'w, 's>,
+    training_evw: EventWriter<'w, EvolutionTrainingEvent>,
+    build_conf: ResMut<'w, CreatureBuilderConfig>,
+    limb_info_save: Local<'s, HashMap<Entity, (f32, f32)>>,
+    environment: ResMut<'w, EnvironmentConfig>,
+    cache: Option<ResMut<'w, FitnessCache>>,
+    recorder: Option<ResMut<'w, TrajectoryRecorder>>,
+}
+
+impl<'w, 's, F: EvolutionFitnessEval + Send + Sync + Default + 'static> GenerationTest<'w, 's, F> {
+    /// Moves the test of the generation along by one step, building creatures
+    /// with meshes when given the assets for them
+    fn step(&mut self, mut assets: Option<CreatureAssets>) {
+        let Self {
+            commands,
+            generation,
+            config,
+            state,
+            next_state,
+            limbs,
+            ground,
+            creature_state,
+            training_evw,
+            build_conf,
+            limb_info_save,
+            environment,
+            cache,
+            recorder,
+        } = self;
+
+        match state.get() {
+            EvolutionState::EvaluatingCreature => {
+                match generation.current_test {
+                    Some(i) => {
+                        let input =
+                            creature_state.input(generation.population[i].creature, generation.current_train_time, config.test_time);
+                        let terminated = generation.terminated_fitness.take();
+                        let fitness = generation.current_fitness.as_ref().unwrap();
+                        let mut objectives = fitness.final_objectives(input.clone());
+                        let eval = match terminated {
+                            Some(eval) => {
+                                objectives.iter_mut().for_each(|objective| *objective = eval);
+                                eval
+                            },
+                            None => fitness.final_eval(input),
+                        };
+                        let behavior = generation.current_behavior.descriptor();
+                        let traits = generation.current_behavior.traits(environment.timestep);
+                        if let Some(recorder) = recorder.as_mut() {
+                            recorder.finish_trial(generation.population[i].creature.0, generation.current_trials.len(), eval);
+                        }
+                        let test = TestResult { fitness: eval, objectives, behavior, traits, ..Default::default() };
+                        if finish_trial(generation.as_mut(), config, cache.as_deref_mut(), i, test) {
+                            generation.current_test = Some(i + 1);
+                        }
+                        generation.current_fitness = Some(generation.new_fitness());
+                    },
+                    None => {
+                        generation.fitnesses.clear();
+                        generation.objectives.clear();
+                        generation.behaviors.clear();
+                        generation.traits.clear();
+                        generation.trial_fitnesses.clear();
+                        generation.diagnostics.clear();
+                        generation.current_trials.clear();
+                        generation.base_terrain = environment.terrain.clone();
+                        generation.current_fitness = Some(generation.new_fitness());
+                        generation.current_test = Some(0);
+                        if let Some(cache) = cache.as_mut() {
+                            cache.retain(&generation.population.iter().map(morphology_hash).collect());
+                        }
+                    },
+                };
+                skip_cached(generation.as_mut(), cache.as_deref(), training_evw);
+                if generation.current_test.unwrap() < generation.population.len() {
+                    if let Some(id) = generation.current_creature {
+                        limbs
+                            .iter()
+                            .filter(|(_, limb, _, _)| limb.creature == id)
+                            .for_each(|(entity, ..)| commands.entity(entity).despawn());
                     }
-                    for mut friction in ground.iter_mut() {
-                        friction.coefficient = 0.0;
+                    if let Some(block) = generation.current_block.take() {
+                        commands.entity(block).despawn();
                     }
-                }
-                next_state.set(EvolutionState::TestingCreature);
-            } else {
-                generation.current_test = None;
-                generation.current_fitness = None;
-                training_evw.send(EvolutionTrainingEvent::StartTestingGeneration(generation.current_generation));
-                next_state.set(EvolutionState::WritingGeneration);
-            }
-        },
-        EvolutionState::TestingCreature => {
-            let index = generation.current_test.unwrap();
-            let morph = &generation.population[index];
-            let creature_id = morph.creature;
-
-            let limb_pos_vels: Vec<_> = limbs
-                .iter()
-                .filter(|(_, limb, _, _, _, _)| limb.creature == creature_id)
-                .map(|(_, _, pos, vel, _, _)| (*pos, *vel))
-                .collect();
-
-            if generation.waiting_for_fall {
-                generation.fall_wait_time += 1;
-                if generation.fall_start_counter < 30 {
-                    generation.fall_start_counter += 1;
-                } else {
-                    let mut y_vel = 0.0;
-                    limb_pos_vels.iter().for_each(|x| {
-                        y_vel += x.1.linvel.y;
+                    let morph = &generation.population[generation.current_test.unwrap()];
+                    let (mut result, creature) = (morph.evaluate(), morph.creature);
+                    generation.current_creature = Some(creature);
+                    generation.current_behavior = BehaviorTracker::default();
+                    generation.current_train_time = 0;
+                    let first_trial = generation.current_trials.is_empty();
+                    generation.current_diagnostics.restart(first_trial);
+                    result.align_to_ground();
+                    perturb_trial(generation.as_ref(), config, environment, &mut result);
+                    generation.current_block = config.push.as_ref().map(|push| {
+                        let assets = assets.as_mut().map(|(meshes, materials)| (meshes.as_mut(), materials.as_mut()));
+                        push.spawn(commands, creature, environment.terrain.as_ref(), assets)
                     });
-                    if y_vel.abs() < 0.01 || generation.fall_wait_time > config.wait_for_fall_timeout {
-                        generation.waiting_for_fall = false;
-                        build_conf.behavior.disable_behavior = false;
-                        generation.fall_wait_time = 0;
-                        generation.fall_start_counter = 0;
-
-                        for (entity, _, _, _, mut friction, mut restitution) in limbs.iter_mut() {
-                            let Some((f, r)) = limb_info_save.get(&entity) else { continue };
-                            friction.coefficient = *f;
-                            restitution.coefficient = *r;
+                    match assets.as_mut() {
+                        Some((meshes, materials)) => {
+                            let color = generation.populate_flags[generation.current_test.unwrap()].into_color();
+                            result.build(commands, meshes, materials, color);
+                        },
+                        None => result.build_nowindow(commands),
+                    }
+                    if config.wait_for_fall {
+                        build_conf.behavior.disable_behavior = true;
+                        generation.waiting_for_fall = true;
+                        for (entity, _, mut friction, mut restitution) in limbs.iter_mut() {
+                            limb_info_save.insert(entity, (friction.coefficient, restitution.coefficient));
+                            friction.coefficient = 0.0;
+                            restitution.coefficient = 0.0;
                         }
                         for mut friction in ground.iter_mut() {
-                            friction.coefficient = 0.75;
+                            friction.coefficient = 0.0;
                         }
-                        limb_info_save.clear();
+                    }
+                    next_state.set(EvolutionState::TestingCreature);
+                } else {
+                    if environment.terrain != generation.base_terrain {
+                        environment.terrain = generation.base_terrain.clone();
+                    }
+                    generation.current_test = None;
+                    generation.current_fitness = None;
+                    next_state.set(EvolutionState::WritingGeneration);
+                }
+            },
+            EvolutionState::TestingCreature => {
+                let index = generation.current_test.unwrap();
+                let morph = &generation.population[index];
+                let creature_id = morph.creature;
+
+                let input = creature_state.input(creature_id, generation.current_train_time, config.test_time);
+
+                if generation.waiting_for_fall {
+                    generation.fall_wait_time += 1;
+                    if generation.fall_start_counter < 30 {
+                        generation.fall_start_counter += 1;
+                    } else {
+                        let mut y_vel = 0.0;
+                        input.limbs.iter().for_each(|x| {
+                            y_vel += x.1.linvel.y;
+                        });
+                        if y_vel.abs() < 0.01 || generation.fall_wait_time > config.wait_for_fall_timeout {
+                            generation.waiting_for_fall = false;
+                            build_conf.behavior.disable_behavior = false;
+                            generation.fall_wait_time = 0;
+                            generation.fall_start_counter = 0;
 
-                        generation
-                            .current_fitness
-                            .as_mut()
-                            .unwrap()
-                            .eval_start(FitnessEvalInput { limbs: limb_pos_vels, test_time: config.test_time });
+                            for (entity, _, mut friction, mut restitution) in limbs.iter_mut() {
+                                let Some((f, r)) = limb_info_save.get(&entity) else { continue };
+                                friction.coefficient = *f;
+                                restitution.coefficient = *r;
+                            }
+                            for mut friction in ground.iter_mut() {
+                                friction.coefficient = environment.ground_friction;
+                            }
+                            limb_info_save.clear();
+
+                            generation.current_fitness.as_mut().unwrap().eval_start(input);
+                        }
                     }
+                    return;
                 }
-                return;
-            }
 
-            generation.current_train_time += 1;
+                generation.current_train_time += 1;
 
-            generation
-                .current_fitness
-                .as_mut()
-                .unwrap()
-                .eval_continuous(FitnessEvalInput { limbs: limb_pos_vels, test_time: config.test_time });
+                let input = FitnessEvalInput { step: generation.current_train_time, ..input };
+                let terminated = terminate_early(generation.as_mut(), config, &input);
+                if !terminated {
+                    generation.current_behavior.record(&input, input.ground_contact_ratio());
+                    if let Some(recorder) = recorder.as_mut() {
+                        recorder.record(&input);
+                    }
+                    generation.current_fitness.as_mut().unwrap().eval_continuous(input);
+                }
 
-            if generation.current_train_time > config.test_time {
-                generation.current_train_time = 0;
-                training_evw.send(EvolutionTrainingEvent::FinishedTestingCreature);
-                next_state.set(EvolutionState::EvaluatingCreature);
-            }
-        },
+                if terminated || generation.current_train_time > config.test_time {
+                    if generation.current_trials.len() + 1 >= config.trials.max(1) {
+                        training_evw.send(EvolutionTrainingEvent::FinishedTestingCreature);
+                    }
+                    next_state.set(EvolutionState::EvaluatingCreature);
+                }
+            },
 
-        _ => (),
+            _ => (),
+        }
     }
 }
+
+
+pub(crate) fn test_generation_nowindow<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(mut test: GenerationTest<F>) {
+    test.step(None);
+}
+
+
+pub(crate) fn test_generation<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
+    mut test: GenerationTest<F>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    test.step(Some((&mut meshes, &mut materials)));
+}
@@ -0,0 +1,58 @@
+use creature_builder::builder::node::CreatureMorphologyGraph;
+use serde::{Deserialize, Serialize};
+
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HallOfFameEntry {
+    pub creature: CreatureMorphologyGraph,
+    pub fitness: f32,
+    /// The generation the creature reached this fitness in
+    pub generation: usize,
+}
+
+
+/// The fittest creatures seen over a whole session, fittest first
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HallOfFame {
+    /// The most creatures the hall holds
+    pub size: usize,
+    pub entries: Vec<HallOfFameEntry>,
+}
+
+impl Default for HallOfFame {
+    fn default() -> Self {
+        Self { size: 10, entries: Vec::new() }
+    }
+}
+
+impl HallOfFame {
+    pub fn new(size: usize) -> Self {
+        Self { size, ..Default::default() }
+    }
+
+    /// Adds the creature if there is room or it is fitter than the least fit
+    /// entry, returning whether it was added. A creature already in the hall
+    /// keeps the best fitness it has reached
+    pub fn insert(&mut self, creature: &CreatureMorphologyGraph, fitness: f32, generation: usize) -> bool {
+        if !fitness.is_finite() || self.size == 0 {
+            return false;
+        }
+        let outclassed = self.entries.len() >= self.size && self.entries.last().is_some_and(|entry| entry.fitness >= fitness);
+        match self.entries.iter_mut().find(|entry| entry.creature.creature == creature.creature) {
+            Some(entry) if entry.fitness >= fitness => return false,
+            Some(entry) => {
+                entry.fitness = fitness;
+                entry.generation = generation;
+            },
+            None if outclassed => return false,
+            None => self.entries.push(HallOfFameEntry { creature: creature.clone(), fitness, generation }),
+        }
+        self.entries.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+        self.entries.truncate(self.size);
+        true
+    }
+
+    pub fn best(&self) -> Option<&HallOfFameEntry> {
+        self.entries.first()
+    }
+}
@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+use creature_builder::CreatureId;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    fitness::EvolutionFitnessEval,
+    generation::EvolutionGeneration,
+    populate::{populate, CreaturePopulateFlag, GenerationPopulator, SelectionMethod},
+};
+
+
+/// Several sub-populations that evolve independently, each with its own
+/// populator, and exchange their best creatures every few generations.
+///
+/// The islands share one `EvolutionGeneration`, where every creature is tagged
+/// with the island it belongs to
+#[derive(Resource)]
+pub struct IslandModel {
+    pub islands: Vec<GenerationPopulator>,
+    /// The number of generations between migrations
+    pub migration_interval: usize,
+    /// The number of retained creatures each island sends to the next
+    pub migrants: usize,
+}
+
+impl IslandModel {
+    pub fn new(islands: Vec<GenerationPopulator>) -> Self {
+        Self { islands, migration_interval: 10, migrants: 2 }
+    }
+
+    pub fn with_migration(mut self, migration_interval: usize, migrants: usize) -> Self {
+        self.migration_interval = migration_interval;
+        self.migrants = migrants;
+        self
+    }
+
+    /// Populates each island from its part of the generation with its own
+    /// populator, migrating creatures between them when it is time to
+    pub(crate) fn populate<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
+        &mut self,
+        generation: &mut EvolutionGeneration<F>,
+        populator: &mut GenerationPopulator,
+    ) {
+        let mut parts = split_islands(generation, self.islands.len());
+
+        let mut best: Option<(f32, usize)> = None;
+        for (island, part) in self.islands.iter_mut().zip(parts.iter_mut()) {
+            let tested = !part.fitnesses.is_empty();
+            // Creature ids are shared between islands
+            island.current_id = populator.current_id;
+            populate(part, island);
+            populator.current_id = island.current_id;
+
+            if tested && best.map_or(true, |(fitness, _)| island.best_fitness > fitness) {
+                best = Some((island.best_fitness, island.best_creature));
+            }
+        }
+        if let Some((fitness, creature)) = best {
+            populator.best_fitness = fitness;
+            populator.best_creature = creature;
+        }
+
+        let interval = self.migration_interval.max(1);
+        if generation.current_generation > 0 && generation.current_generation % interval == 0 {
+            self.migrate(&mut parts, populator);
+        }
+
+        merge_islands(generation, parts);
+    }
+
+    /// Copies the first retained creatures of each island over the last mutated
+    /// creatures of the next island in a ring
+    fn migrate<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
+        &mut self,
+        parts: &mut [EvolutionGeneration<F>],
+        populator: &mut GenerationPopulator,
+    ) {
+        let emigrants: Vec<Vec<_>> = parts
+            .iter()
+            .map(|part| {
+                part.population
+                    .iter()
+                    .zip(part.populate_flags.iter())
+                    .filter(|(_, flag)| matches!(flag, CreaturePopulateFlag::Retained))
+                    .take(self.migrants)
+                    .map(|(creature, _)| creature.clone())
+                    .collect()
+            })
+            .collect();
+
+        let n = parts.len();
+        for (i, creatures) in emigrants.into_iter().enumerate() {
+            let target = (i + 1) % n;
+            if target == i {
+                continue;
+            }
+            let part = &mut parts[target];
+            for mut creature in creatures {
+                let Some(slot) = part.populate_flags.iter().rposition(|flag| matches!(flag, CreaturePopulateFlag::Mutated)) else { break };
+                creature.creature = CreatureId(populator.current_id);
+                populator.current_id += 1;
+                part.population[slot] = creature;
+                part.populate_flags[slot] = CreaturePopulateFlag::Migrated;
+            }
+            if let Some(speciation) = self.islands[target].speciation.as_mut() {
+                part.species = speciation.speciate(&part.population);
+            }
+        }
+    }
+}
+
+
+/// The populator settings of one island. Any setting left unset keeps the
+/// value shared by every island
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IslandSettings {
+    pub elitism: Option<f32>,
+    pub rand_percent: Option<f32>,
+    pub num_mutations: Option<usize>,
+    pub selection: Option<SelectionMethod>,
+}
+
+impl IslandSettings {
+    /// Loads the settings of each island, in order, from a RON list
+    pub fn load(path: &str) -> Result<Vec<Self>, String> {
+        let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        ron::de::from_str(&data).map_err(|e| e.to_string())
+    }
+
+    pub fn apply(&self, mut populator: GenerationPopulator) -> GenerationPopulator {
+        populator.elitism = self.elitism.unwrap_or(populator.elitism);
+        populator.rand_percent = self.rand_percent.unwrap_or(populator.rand_percent);
+        populator.num_mutations = self.num_mutations.unwrap_or(populator.num_mutations);
+        populator.selection = self.selection.unwrap_or(populator.selection);
+        populator
+    }
+}
+
+
+/// Moves each creature's records into the generation of its island. Creatures
+/// without an island, as in sessions started without islands, are divided
+/// evenly between them
+fn split_islands<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
+    generation: &mut EvolutionGeneration<F>,
+    n: usize,
+) -> Vec<EvolutionGeneration<F>> {
+    let len = generation.population.len();
+    let tags: Vec<usize> = if generation.islands.len() == len {
+        generation.islands.iter().map(|island| island % n).collect()
+    } else {
+        (0..len).map(|i| i * n / len).collect()
+    };
+
+    fn distribute<T>(values: Vec<T>, tags: &[usize], n: usize) -> Vec<Vec<T>> {
+        let mut parts: Vec<Vec<T>> = (0..n).map(|_| Vec::new()).collect();
+        // Records missing from resumed sessions stay missing
+        if values.len() == tags.len() {
+            for (value, tag) in values.into_iter().zip(tags) {
+                parts[*tag].push(value);
+            }
+        }
+        parts
+    }
+
+    let mut population = distribute(std::mem::take(&mut generation.population), &tags, n).into_iter();
+    let mut fitnesses = distribute(std::mem::take(&mut generation.fitnesses), &tags, n).into_iter();
+    let mut objectives = distribute(std::mem::take(&mut generation.objectives), &tags, n).into_iter();
+    let mut behaviors = distribute(std::mem::take(&mut generation.behaviors), &tags, n).into_iter();
+    let mut traits = distribute(std::mem::take(&mut generation.traits), &tags, n).into_iter();
+    let mut species = distribute(std::mem::take(&mut generation.species), &tags, n).into_iter();
+    let mut populate_flags = distribute(std::mem::take(&mut generation.populate_flags), &tags, n).into_iter();
+    generation.islands.clear();
+
+    (0..n)
+        .map(|_| EvolutionGeneration {
+            population: population.next().unwrap(),
+            fitnesses: fitnesses.next().unwrap(),
+            objectives: objectives.next().unwrap(),
+            behaviors: behaviors.next().unwrap(),
+            traits: traits.next().unwrap(),
+            species: species.next().unwrap(),
+            populate_flags: populate_flags.next().unwrap(),
+            current_generation: generation.current_generation,
+            ..Default::default()
+        })
+        .collect()
+}
+
+
+/// Joins the generations of each island back into one, tagging each creature
+/// with its island
+fn merge_islands<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
+    generation: &mut EvolutionGeneration<F>,
+    parts: Vec<EvolutionGeneration<F>>,
+) {
+    for (island, part) in parts.into_iter().enumerate() {
+        generation.islands.extend(std::iter::repeat(island).take(part.population.len()));
+        generation.population.extend(part.population);
+        generation.fitnesses.extend(part.fitnesses);
+        generation.objectives.extend(part.objectives);
+        generation.behaviors.extend(part.behaviors);
+        generation.traits.extend(part.traits);
+        generation.species.extend(part.species);
+        generation.populate_flags.extend(part.populate_flags);
+    }
+}
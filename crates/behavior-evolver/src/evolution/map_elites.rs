@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+
+use creature_builder::builder::node::CreatureMorphologyGraph;
+use rand::{rngs::ThreadRng, Rng};
+use serde::{Deserialize, Serialize};
+
+
+/// Measurements of a creature's body and behavior that MAP-Elites can arrange
+/// creatures by
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CreatureTraits {
+    pub limb_count: usize,
+    /// The mean height of the center of mass
+    pub height: f32,
+    /// The mean horizontal speed of the center of mass
+    pub speed: f32,
+    /// The portion of limbs touching the ground, averaged over the test
+    pub contact_ratio: f32,
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CreatureTrait {
+    LimbCount,
+    Height,
+    Speed,
+    ContactRatio,
+}
+
+impl CreatureTrait {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "limbs" => Some(Self::LimbCount),
+            "height" => Some(Self::Height),
+            "speed" => Some(Self::Speed),
+            "contact" => Some(Self::ContactRatio),
+            _ => None,
+        }
+    }
+
+    pub fn value(&self, traits: &CreatureTraits) -> f32 {
+        match self {
+            Self::LimbCount => traits.limb_count as f32,
+            Self::Height => traits.height,
+            Self::Speed => traits.speed,
+            Self::ContactRatio => traits.contact_ratio,
+        }
+    }
+}
+
+
+/// One axis of the MAP-Elites grid, splitting `[min, max]` into `bins` equal
+/// cells. Values outside the range fall into the first or last cell
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EliteDimension {
+    pub feature: CreatureTrait,
+    pub min: f32,
+    pub max: f32,
+    pub bins: usize,
+}
+
+impl EliteDimension {
+    /// A dimension over the usual range of the given trait
+    pub fn new(feature: CreatureTrait) -> Self {
+        let (min, max, bins) = match feature {
+            CreatureTrait::LimbCount => (1.0, 21.0, 10),
+            CreatureTrait::Height => (0.0, 4.0, 8),
+            CreatureTrait::Speed => (0.0, 5.0, 10),
+            CreatureTrait::ContactRatio => (0.0, 1.0, 10),
+        };
+        Self { feature, min, max, bins }
+    }
+
+    pub fn bin(&self, traits: &CreatureTraits) -> usize {
+        let t = (self.feature.value(traits) - self.min) / (self.max - self.min);
+        if t.is_finite() {
+            ((t * self.bins as f32).max(0.0) as usize).min(self.bins.max(1) - 1)
+        } else {
+            0
+        }
+    }
+}
+
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EliteCell {
+    pub creature: CreatureMorphologyGraph,
+    pub fitness: f32,
+    pub traits: CreatureTraits,
+}
+
+
+/// A grid over the chosen traits where each cell keeps the fittest creature
+/// found with those traits
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct MapElites {
+    pub dimensions: Vec<EliteDimension>,
+    pub cells: BTreeMap<Vec<usize>, EliteCell>,
+}
+
+impl MapElites {
+    pub fn new(dimensions: Vec<EliteDimension>) -> Self {
+        Self { dimensions, cells: BTreeMap::new() }
+    }
+
+    pub fn cell(&self, traits: &CreatureTraits) -> Vec<usize> {
+        self.dimensions.iter().map(|dimension| dimension.bin(traits)).collect()
+    }
+
+    /// Places the creature in its cell if the cell is empty or holds a less fit
+    /// creature, returning whether it was placed
+    pub fn insert(&mut self, creature: &CreatureMorphologyGraph, fitness: f32, traits: CreatureTraits) -> bool {
+        let cell = self.cell(&traits);
+        if self.cells.get(&cell).is_some_and(|elite| elite.fitness >= fitness) || !fitness.is_finite() {
+            return false;
+        }
+        self.cells.insert(cell, EliteCell { creature: creature.clone(), fitness, traits });
+        true
+    }
+
+    pub fn random_elite(&self, rng: &mut ThreadRng) -> Option<&EliteCell> {
+        if self.cells.is_empty() {
+            return None;
+        }
+        self.cells.values().nth(rng.gen_range(0..self.cells.len()))
+    }
+}
@@ -1,7 +1,22 @@
+pub mod cache;
+pub mod competition;
+pub mod curriculum;
+pub mod diagnostics;
 pub mod fitness;
+pub mod fluid;
 pub mod generation;
+pub mod hall_of_fame;
+pub mod island;
+pub mod map_elites;
+pub mod novelty;
+pub mod nsga;
 pub mod populate;
+pub mod push;
+pub mod species;
 pub mod state;
+pub mod terrain;
+pub mod trajectory;
+pub mod trials;
 pub mod write;
 
 use std::marker::PhantomData;
@@ -10,8 +25,8 @@ use bevy::prelude::*;
 use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
 use bevy_rapier3d::{
     dynamics::{CoefficientCombineRule, GravityScale, RigidBody, Velocity},
-    geometry::{ActiveEvents, ActiveHooks, Collider, ColliderMassProperties, Friction, Restitution},
-    plugin::{RapierConfiguration, RapierPhysicsPlugin, TimestepMode},
+    geometry::{ActiveEvents, ActiveHooks, ColliderMassProperties, Friction, Restitution},
+    plugin::{PhysicsSet, RapierConfiguration, RapierPhysicsPlugin, TimestepMode},
 };
 use bevy_screen_diagnostics::{ScreenDiagnosticsPlugin, ScreenFrameDiagnosticsPlugin};
 use creature_builder::{
@@ -19,55 +34,147 @@ use creature_builder::{
     sensor::{ContactFilter, ContactFilterTag},
     CreatureBuilderPlugin,
 };
+use serde::{Deserialize, Serialize};
 
 use self::{
+    competition::Tournament,
+    curriculum::{advance_curriculum, Curriculum},
+    diagnostics::{measure_physics, penalize_exploits},
     fitness::EvolutionFitnessEval,
+    fluid::{fluid_drag, FluidConfig},
     generation::{test_generation, test_generation_nowindow, EvolutionGeneration, GenerationTestingConfig},
     populate::{populate_generation, GenerationPopulator},
     state::{begin_training_session, EvolutionState, EvolutionTrainingEvent},
+    terrain::Terrain,
     write::write_generation,
 };
 
 pub struct CreatureEvolutionPlugin<F: EvolutionFitnessEval + Send + Sync + Default + 'static> {
     pub window: bool,
+    pub environment: EnvironmentConfig,
+    /// The weights of the terms of the fitness, until a curriculum stage sets
+    /// its own. Empty keeps the weights a resumed session was trained with
+    pub fitness_weights: Vec<f32>,
+    /// The direction directional fitnesses reward moving in. Unset keeps the
+    /// heading a resumed session was trained with, or +x
+    pub fitness_heading: Option<Vec2>,
     _p: PhantomData<F>,
 }
 
 impl<F: EvolutionFitnessEval + Send + Sync + Default + 'static> Default for CreatureEvolutionPlugin<F> {
     fn default() -> Self {
-        Self { window: false, _p: PhantomData }
+        Self {
+            window: false,
+            environment: EnvironmentConfig::default(),
+            fitness_weights: Vec::new(),
+            fitness_heading: None,
+            _p: PhantomData,
+        }
     }
 }
 
 impl<F: EvolutionFitnessEval + Send + Sync + Default + 'static> CreatureEvolutionPlugin<F> {
     pub fn new(window: bool) -> Self {
-        Self { window, _p: PhantomData }
+        Self { window, ..Default::default() }
+    }
+
+    pub fn with_environment(mut self, environment: EnvironmentConfig) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    pub fn with_fitness_weights(mut self, fitness_weights: Vec<f32>) -> Self {
+        self.fitness_weights = fitness_weights;
+        self
+    }
+
+    pub fn with_fitness_heading(mut self, heading: Option<Vec2>) -> Self {
+        self.fitness_heading = heading;
+        self
     }
 }
 
 impl<F: EvolutionFitnessEval + Send + Sync + Default + 'static> Plugin for CreatureEvolutionPlugin<F> {
     fn build(&self, app: &mut App) {
-        app.add_plugins(CreatureEnvironmentPlugin { window: self.window })
+        app.add_plugins(CreatureEnvironmentPlugin { window: self.window, environment: self.environment.clone() })
             .add_state::<EvolutionState>()
-            .init_resource::<EvolutionGeneration<F>>()
+            .insert_resource(EvolutionGeneration::<F> {
+                fitness_weights: self.fitness_weights.clone(),
+                fitness_heading: self.fitness_heading,
+                ..Default::default()
+            })
             .init_resource::<GenerationPopulator>()
             .init_resource::<GenerationTestingConfig>()
+            .init_resource::<Curriculum>()
             .add_event::<EvolutionTrainingEvent>()
             .add_systems(OnEnter(EvolutionState::BeginTrainingSession), begin_training_session::<F>)
-            .add_systems(OnEnter(EvolutionState::WritingGeneration), write_generation::<F>)
-            .add_systems(OnEnter(EvolutionState::PopulatingGeneration), populate_generation::<F>);
+            .add_systems(OnEnter(EvolutionState::WritingGeneration), (penalize_exploits::<F>, write_generation::<F>).chain())
+            .add_systems(OnEnter(EvolutionState::PopulatingGeneration), (advance_curriculum::<F>, populate_generation::<F>).chain())
+            .add_systems(
+                Update,
+                measure_physics::<F>.run_if(in_state(EvolutionState::TestingCreature)).run_if(not(resource_exists::<Tournament>())),
+            );
 
         if self.window {
-            app.add_systems(Update, test_generation::<F>);
+            app.add_systems(Update, test_generation::<F>.run_if(not(resource_exists::<Tournament>())).before(EnvironmentSet));
         } else {
-            app.add_systems(Update, test_generation_nowindow::<F>);
+            app.add_systems(Update, test_generation_nowindow::<F>.run_if(not(resource_exists::<Tournament>())).before(EnvironmentSet));
         }
     }
 }
 
 
+/// The physics and world settings creatures are tested in
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EnvironmentConfig {
+    pub gravity: Vec3,
+    /// The length of a physics step in seconds
+    pub timestep: f32,
+    pub substeps: usize,
+    pub ground_friction: f32,
+    pub ground_restitution: f32,
+    /// The ground, if any
+    pub terrain: Option<Terrain>,
+    /// The fluid limbs move through, if any
+    pub fluid: Option<FluidConfig>,
+}
+
+impl Default for EnvironmentConfig {
+    fn default() -> Self {
+        Self {
+            gravity: Vec3::Y * -9.81,
+            timestep: 1.0 / 60.0,
+            substeps: 4,
+            ground_friction: 0.75,
+            ground_restitution: 0.0,
+            terrain: Some(Terrain::Flat),
+            fluid: None,
+        }
+    }
+}
+
+impl EnvironmentConfig {
+    /// A weightless fluid with no ground that resists the movement of limbs
+    pub fn water() -> Self {
+        Self { gravity: Vec3::ZERO, terrain: None, fluid: Some(FluidConfig::default()), ..Default::default() }
+    }
+
+    pub fn with_terrain(mut self, terrain: Terrain) -> Self {
+        self.terrain = Some(terrain);
+        self
+    }
+
+    pub fn load(path: &str) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        ron::de::from_str(&data).map_err(|e| e.to_string())
+    }
+}
+
+
 pub struct CreatureEnvironmentPlugin {
     pub window: bool,
+    pub environment: EnvironmentConfig,
 }
 
 impl Plugin for CreatureEnvironmentPlugin {
@@ -75,15 +182,20 @@ impl Plugin for CreatureEnvironmentPlugin {
         app.add_plugins(CreatureBuilderPlugin)
             .insert_resource(CreatureBuilderConfig::default())
             .add_plugins(RapierPhysicsPlugin::<ContactFilter>::default())
-            .insert_resource(RapierConfiguration { timestep_mode: TimestepMode::Fixed { dt: 1.0 / 60.0, substeps: 4 }, ..default() });
+            .insert_resource(self.environment.clone());
+
+        if self.window {
+            app.add_systems(Update, spawn_environment.run_if(resource_changed::<EnvironmentConfig>()).in_set(EnvironmentSet));
+        } else {
+            app.add_systems(Update, spawn_environment_nowindow.run_if(resource_changed::<EnvironmentConfig>()).in_set(EnvironmentSet));
+        }
+        app.add_systems(PostUpdate, fluid_drag.run_if(resource_exists::<FluidConfig>()).before(PhysicsSet::SyncBackend));
 
         if self.window {
             app.add_plugins(PanOrbitCameraPlugin)
                 .add_plugins(ScreenDiagnosticsPlugin::default())
-                .add_systems(Startup, (setup, setup_ground))
+                .add_systems(Startup, setup)
                 .add_plugins(ScreenFrameDiagnosticsPlugin);
-        } else {
-            app.add_systems(Startup, setup_ground_nowindow);
         }
     }
 }
@@ -93,53 +205,100 @@ impl Plugin for CreatureEnvironmentPlugin {
 pub struct GroundMarker;
 
 
-fn setup_ground_nowindow(mut commands: Commands) {
-    commands.spawn((
-        RigidBody::KinematicPositionBased,
-        Velocity { linvel: Vec3::ZERO, angvel: Vec3::ZERO },
-        GravityScale(1.0),
-        ActiveEvents::COLLISION_EVENTS,
-        ActiveHooks::FILTER_CONTACT_PAIRS,
-        ContactFilterTag::GroundGroup,
-        Collider::cuboid(500.0, 5.0, 500.0),
-        Friction { coefficient: 0.75, combine_rule: CoefficientCombineRule::Average },
-        Restitution { coefficient: 0.0, combine_rule: CoefficientCombineRule::Average },
-        ColliderMassProperties::Density(1.0),
-        Transform::from_xyz(0.0, -5.0, 0.0),
-        GlobalTransform::default(),
-        GroundMarker,
-        Name::new("Ground"),
-    ));
+/// Rebuilds the ground whenever the environment changes. Systems that change
+/// the environment while a creature is spawned run before it, so the creature
+/// never takes a physics step on the previous ground
+#[derive(SystemSet, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EnvironmentSet;
+
+
+/// Replaces the ground and physics settings with those of the environment
+fn apply_environment(
+    commands: &mut Commands,
+    environment: &EnvironmentConfig,
+    rapier: &mut RapierConfiguration,
+    ground: &Query<Entity, With<GroundMarker>>,
+) {
+    rapier.gravity = environment.gravity;
+    rapier.timestep_mode = TimestepMode::Fixed { dt: environment.timestep, substeps: environment.substeps };
+
+    match &environment.fluid {
+        Some(fluid) => commands.insert_resource(fluid.clone()),
+        None => commands.remove_resource::<FluidConfig>(),
+    }
+
+    for entity in ground.iter() {
+        commands.entity(entity).despawn();
+    }
 }
 
 
-fn setup_ground(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>, mut materials: ResMut<Assets<StandardMaterial>>) {
-    commands.spawn((
-        RigidBody::KinematicPositionBased,
-        Velocity { linvel: Vec3::ZERO, angvel: Vec3::ZERO },
-        GravityScale(1.0),
-        ActiveEvents::COLLISION_EVENTS,
-        ActiveHooks::FILTER_CONTACT_PAIRS,
-        ContactFilterTag::GroundGroup,
-        Collider::cuboid(500.0, 5.0, 500.0),
-        Friction { coefficient: 0.75, combine_rule: CoefficientCombineRule::Average },
-        Restitution { coefficient: 0.0, combine_rule: CoefficientCombineRule::Average },
-        ColliderMassProperties::Density(1.0),
-        PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Box::new(1000.0, 10.0, 1000.0))),
-            material: materials.add(StandardMaterial {
-                base_color: Color::rgb(0.1, 0.1, 0.1),
-                perceptual_roughness: 0.9,
-                reflectance: 0.1,
-                metallic: 0.0,
-                ..default()
-            }),
-            transform: Transform::from_xyz(0.0, -5.0, 0.0),
-            ..default()
-        },
-        GroundMarker,
-        Name::new("Ground"),
-    ));
+fn spawn_environment_nowindow(
+    mut commands: Commands,
+    environment: Res<EnvironmentConfig>,
+    mut rapier: ResMut<RapierConfiguration>,
+    ground: Query<Entity, With<GroundMarker>>,
+) {
+    apply_environment(&mut commands, &environment, &mut rapier, &ground);
+
+    let Some(terrain) = &environment.terrain else { return };
+    for piece in terrain.pieces() {
+        commands.spawn((
+            RigidBody::KinematicPositionBased,
+            Velocity { linvel: Vec3::ZERO, angvel: Vec3::ZERO },
+            GravityScale(1.0),
+            ActiveEvents::COLLISION_EVENTS,
+            ActiveHooks::FILTER_CONTACT_PAIRS,
+            ContactFilterTag::GroundGroup,
+            piece.shape.collider(),
+            Friction { coefficient: environment.ground_friction, combine_rule: CoefficientCombineRule::Average },
+            Restitution { coefficient: environment.ground_restitution, combine_rule: CoefficientCombineRule::Average },
+            ColliderMassProperties::Density(1.0),
+            piece.transform,
+            GlobalTransform::default(),
+            GroundMarker,
+            Name::new("Ground"),
+        ));
+    }
+}
+
+
+fn spawn_environment(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    environment: Res<EnvironmentConfig>,
+    mut rapier: ResMut<RapierConfiguration>,
+    ground: Query<Entity, With<GroundMarker>>,
+) {
+    apply_environment(&mut commands, &environment, &mut rapier, &ground);
+
+    let Some(terrain) = &environment.terrain else { return };
+    let material = materials.add(StandardMaterial {
+        base_color: Color::rgb(0.1, 0.1, 0.1),
+        perceptual_roughness: 0.9,
+        reflectance: 0.1,
+        metallic: 0.0,
+        ..default()
+    });
+
+    for piece in terrain.pieces() {
+        commands.spawn((
+            RigidBody::KinematicPositionBased,
+            Velocity { linvel: Vec3::ZERO, angvel: Vec3::ZERO },
+            GravityScale(1.0),
+            ActiveEvents::COLLISION_EVENTS,
+            ActiveHooks::FILTER_CONTACT_PAIRS,
+            ContactFilterTag::GroundGroup,
+            piece.shape.collider(),
+            Friction { coefficient: environment.ground_friction, combine_rule: CoefficientCombineRule::Average },
+            Restitution { coefficient: environment.ground_restitution, combine_rule: CoefficientCombineRule::Average },
+            ColliderMassProperties::Density(1.0),
+            PbrBundle { mesh: meshes.add(piece.shape.mesh()), material: material.clone(), transform: piece.transform, ..default() },
+            GroundMarker,
+            Name::new("Ground"),
+        ));
+    }
 }
 
 
@@ -0,0 +1,135 @@
+use bevy::math::{Vec3, Vec3Swizzles};
+use serde::{Deserialize, Serialize};
+
+use super::{fitness::FitnessEvalInput, map_elites::CreatureTraits};
+
+
+/// A summary of what a creature did during its test, used to measure how
+/// different its behavior is from that of other creatures
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BehaviorDescriptor(pub Vec<f32>);
+
+impl BehaviorDescriptor {
+    pub fn distance(&self, other: &Self) -> f32 {
+        self.0.iter().zip(other.0.iter()).map(|(a, b)| (a - b) * (a - b)).sum::<f32>().sqrt()
+    }
+}
+
+
+/// Records the trajectory of a creature's center of mass and its contact with
+/// the ground while it is tested
+#[derive(Clone, Debug, Default)]
+pub struct BehaviorTracker {
+    samples: Vec<Vec3>,
+    /// The portion of limbs touching the ground at each sample
+    contacts: Vec<f32>,
+    limb_count: usize,
+}
+
+impl BehaviorTracker {
+    /// The number of evenly spaced points of the trajectory that make up a
+    /// descriptor
+    const CHECKPOINTS: usize = 4;
+
+    pub fn record(&mut self, input: &FitnessEvalInput, contact_ratio: f32) {
+        self.limb_count = input.limbs.len();
+        self.contacts.push(contact_ratio);
+        self.samples.push(input.center_of_mass());
+    }
+
+    /// The horizontal displacement from the start and the height of the center
+    /// of mass at each checkpoint, the last of which is the end of the test
+    pub fn descriptor(&self) -> BehaviorDescriptor {
+        let Some(start) = self.samples.first() else { return BehaviorDescriptor(vec![0.0; Self::CHECKPOINTS * 3]) };
+        let mut descriptor = Vec::with_capacity(Self::CHECKPOINTS * 3);
+        for i in 1..=Self::CHECKPOINTS {
+            let sample = self.samples[(self.samples.len() - 1) * i / Self::CHECKPOINTS];
+            descriptor.extend_from_slice(&[sample.x - start.x, sample.z - start.z, sample.y]);
+        }
+        BehaviorDescriptor(descriptor)
+    }
+
+    /// The traits of the creature over its test, given the length of a physics
+    /// step in seconds
+    pub fn traits(&self, timestep: f32) -> CreatureTraits {
+        let samples = self.samples.len().max(1) as f32;
+        let path_length: f32 = self.samples.windows(2).map(|pair| (pair[1] - pair[0]).xz().length()).sum();
+        CreatureTraits {
+            limb_count: self.limb_count,
+            height: self.samples.iter().map(|sample| sample.y).sum::<f32>() / samples,
+            speed: path_length / (samples * timestep),
+            contact_ratio: self.contacts.iter().sum::<f32>() / samples,
+        }
+    }
+}
+
+
+/// Rewards creatures for behaving differently from the rest of the population
+/// and from an archive of behaviors seen in earlier generations
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NoveltySearch {
+    /// How much of the selection score is novelty rather than fitness, from 0
+    /// for pure fitness to 1 for pure novelty
+    pub weight: f32,
+    /// The number of nearest neighbors averaged to give the novelty score
+    pub k: usize,
+    /// The novelty a behavior needs to be added to the archive
+    pub archive_threshold: f32,
+    /// The largest the archive can grow, after which the oldest behaviors are
+    /// dropped
+    pub archive_size: usize,
+    pub archive: Vec<BehaviorDescriptor>,
+}
+
+impl Default for NoveltySearch {
+    fn default() -> Self {
+        Self { weight: 0.5, k: 15, archive_threshold: 2.0, archive_size: 1000, archive: Vec::new() }
+    }
+}
+
+impl NoveltySearch {
+    pub fn new(weight: f32) -> Self {
+        Self { weight, ..Default::default() }
+    }
+
+    /// The mean distance from each behavior to its `k` nearest neighbors among
+    /// the rest of the population and the archive
+    pub fn scores(&self, behaviors: &[BehaviorDescriptor]) -> Vec<f32> {
+        behaviors
+            .iter()
+            .enumerate()
+            .map(|(i, behavior)| {
+                let mut distances: Vec<f32> = behaviors
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, other)| other)
+                    .chain(self.archive.iter())
+                    .map(|other| behavior.distance(other))
+                    .collect();
+                if distances.is_empty() {
+                    return 0.0;
+                }
+                distances.sort_unstable_by(|a, b| a.total_cmp(b));
+                distances.truncate(self.k.max(1));
+                distances.iter().sum::<f32>() / distances.len() as f32
+            })
+            .collect()
+    }
+
+    /// Adds the behaviors that were novel enough to the archive
+    pub fn update_archive(&mut self, behaviors: &[BehaviorDescriptor], scores: &[f32]) {
+        for (behavior, score) in behaviors.iter().zip(scores) {
+            if *score >= self.archive_threshold {
+                self.archive.push(behavior.clone());
+            }
+        }
+        let excess = self.archive.len().saturating_sub(self.archive_size);
+        self.archive.drain(..excess);
+    }
+
+    /// Blends a fitness with a novelty score by `weight`
+    pub fn blend(&self, fitness: f32, novelty: f32) -> f32 {
+        fitness * (1.0 - self.weight) + novelty * self.weight
+    }
+}
@@ -0,0 +1,84 @@
+/// Whether `a` is at least as good as `b` in every objective and better in at
+/// least one. Objectives are always maximized
+pub fn dominates(a: &[f32], b: &[f32]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x >= y) && a.iter().zip(b).any(|(x, y)| x > y)
+}
+
+
+/// Splits the population into successive Pareto fronts, the first of which is
+/// not dominated by any other creature
+pub fn non_dominated_sort(objectives: &[Vec<f32>]) -> Vec<Vec<usize>> {
+    let n = objectives.len();
+    let mut dominated_by: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut domination_count = vec![0; n];
+
+    for i in 0..n {
+        for j in (i + 1)..n {
+            if dominates(&objectives[i], &objectives[j]) {
+                dominated_by[i].push(j);
+                domination_count[j] += 1;
+            } else if dominates(&objectives[j], &objectives[i]) {
+                dominated_by[j].push(i);
+                domination_count[i] += 1;
+            }
+        }
+    }
+
+    let mut fronts = Vec::new();
+    let mut front: Vec<usize> = (0..n).filter(|i| domination_count[*i] == 0).collect();
+    while !front.is_empty() {
+        let mut next = Vec::new();
+        for i in front.iter() {
+            for j in dominated_by[*i].iter() {
+                domination_count[*j] -= 1;
+                if domination_count[*j] == 0 {
+                    next.push(*j);
+                }
+            }
+        }
+        fronts.push(front);
+        front = next;
+    }
+    fronts
+}
+
+
+/// The crowding distance of each creature in `front`, in the same order.
+/// Creatures at the edges of any objective are given an infinite distance
+pub fn crowding_distance(objectives: &[Vec<f32>], front: &[usize]) -> Vec<f32> {
+    let mut distances = vec![0.0; front.len()];
+    let Some(n_objectives) = front.first().map(|i| objectives[*i].len()) else { return distances };
+
+    let columns = (0..n_objectives).map(|objective| front.iter().map(|i| objectives[*i][objective]).collect::<Vec<f32>>());
+    for values in columns {
+        let value = |k: usize| values[k];
+        let mut order: Vec<usize> = (0..front.len()).collect();
+        order.sort_by(|a, b| value(*a).total_cmp(&value(*b)));
+
+        let (min, max) = (value(order[0]), value(order[order.len() - 1]));
+        distances[order[0]] = f32::INFINITY;
+        distances[order[order.len() - 1]] = f32::INFINITY;
+        if max - min <= 0.0 {
+            continue;
+        }
+
+        for k in 1..order.len().saturating_sub(1) {
+            distances[order[k]] += (value(order[k + 1]) - value(order[k - 1])) / (max - min);
+        }
+    }
+    distances
+}
+
+
+/// Orders the population from best to worst by front, then by decreasing
+/// crowding distance within each front
+pub fn nsga2_order(objectives: &[Vec<f32>]) -> Vec<usize> {
+    let mut order = Vec::with_capacity(objectives.len());
+    for front in non_dominated_sort(objectives) {
+        let distances = crowding_distance(objectives, &front);
+        let mut ranked: Vec<_> = front.into_iter().zip(distances).collect();
+        ranked.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+        order.extend(ranked.into_iter().map(|(i, _)| i));
+    }
+    order
+}
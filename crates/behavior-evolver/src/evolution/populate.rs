@@ -3,8 +3,15 @@ use creature_builder::CreatureId;
 use serde::{Deserialize, Serialize};
 
 use super::{
-    fitness::EvolutionFitnessEval,
+    diagnostics::ExploitLimits,
+    fitness::{EvolutionFitnessEval, PENALTY_FITNESS},
     generation::EvolutionGeneration,
+    hall_of_fame::HallOfFame,
+    island::IslandModel,
+    map_elites::MapElites,
+    novelty::NoveltySearch,
+    nsga::nsga2_order,
+    species::Speciation,
     state::{EvolutionState, EvolutionTrainingEvent},
 };
 use crate::mutate::{MutateMorphology, MutateMorphologyParams, RandomMorphologyParams};
@@ -26,6 +33,20 @@ pub struct GenerationPopulator {
     pub best_fitness: f32,
     pub best_creature: usize,
     pub num_mutations: usize,
+    pub selection: SelectionMethod,
+    /// Blends the novelty of each creature's behavior into selection, if set
+    pub novelty: Option<NoveltySearch>,
+    /// Keeps the fittest creature of each cell of a grid over creature traits,
+    /// breeding every new creature from it instead of the last generation
+    pub map_elites: Option<MapElites>,
+    /// Divides the population into species that keep and breed from their own
+    /// best creatures, if set
+    pub speciation: Option<Speciation>,
+    /// The fittest creatures of the whole session
+    pub hall_of_fame: HallOfFame,
+    /// Penalizes creatures whose tests show signs of exploiting the physics, if
+    /// set
+    pub exploit_limits: Option<ExploitLimits>,
 }
 
 impl GenerationPopulator {
@@ -44,11 +65,47 @@ impl GenerationPopulator {
             mutate_params,
             rand_params,
             current_id: 0,
-            best_fitness: -1000000000000.0,
+            best_fitness: PENALTY_FITNESS,
             best_creature: 0,
             num_mutations,
+            selection: SelectionMethod::default(),
+            novelty: None,
+            map_elites: None,
+            speciation: None,
+            hall_of_fame: HallOfFame::default(),
+            exploit_limits: None,
         }
     }
+
+    pub fn with_selection(mut self, selection: SelectionMethod) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    pub fn with_novelty(mut self, novelty: NoveltySearch) -> Self {
+        self.novelty = Some(novelty);
+        self
+    }
+
+    pub fn with_map_elites(mut self, map_elites: MapElites) -> Self {
+        self.map_elites = Some(map_elites);
+        self
+    }
+
+    pub fn with_speciation(mut self, speciation: Speciation) -> Self {
+        self.speciation = Some(speciation);
+        self
+    }
+
+    pub fn with_hall_of_fame(mut self, size: usize) -> Self {
+        self.hall_of_fame = HallOfFame::new(size);
+        self
+    }
+
+    pub fn with_exploit_limits(mut self, limits: ExploitLimits) -> Self {
+        self.exploit_limits = Some(limits);
+        self
+    }
 }
 
 impl Default for GenerationPopulator {
@@ -63,15 +120,35 @@ impl Default for GenerationPopulator {
             best_fitness: 0.0,
             best_creature: 0,
             num_mutations: 80,
+            selection: SelectionMethod::default(),
+            novelty: None,
+            map_elites: None,
+            speciation: None,
+            hall_of_fame: HallOfFame::default(),
+            exploit_limits: None,
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+/// How the creatures that are retained into the next generation are chosen
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SelectionMethod {
+    /// Keep the creatures with the highest fitness
+    #[default]
+    Elitism,
+    /// Keep the creatures on the best Pareto fronts of the fitness objectives,
+    /// preferring the least crowded
+    Nsga2,
+}
+
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
 pub enum CreaturePopulateFlag {
     Retained,
     Mutated,
     Spawned,
+    /// Copied from the best creatures of another island
+    Migrated,
 }
 
 impl CreaturePopulateFlag {
@@ -80,6 +157,7 @@ impl CreaturePopulateFlag {
             Self::Retained => Color::rgba_u8(166, 227, 161, 220),
             Self::Mutated => Color::rgba_u8(137, 220, 235, 220),
             Self::Spawned => Color::rgba_u8(249, 226, 175, 220),
+            Self::Migrated => Color::rgba_u8(203, 166, 247, 220),
         }
     }
 }
@@ -88,23 +166,43 @@ impl CreaturePopulateFlag {
 pub(crate) fn populate_generation<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
     mut generation: ResMut<EvolutionGeneration<F>>,
     mut populator: ResMut<GenerationPopulator>,
+    islands: Option<ResMut<IslandModel>>,
     mut next_state: ResMut<NextState<EvolutionState>>,
     mut training_evw: EventWriter<EvolutionTrainingEvent>,
+) {
+    if !generation.population.is_empty() {
+        generation.current_generation += 1;
+    }
+
+    match islands {
+        Some(mut islands) => islands.populate(generation.as_mut(), populator.as_mut()),
+        None => populate(generation.as_mut(), populator.as_mut()),
+    }
+
+    training_evw.send(EvolutionTrainingEvent::StartTestingGeneration(generation.current_generation));
+    next_state.set(EvolutionState::EvaluatingCreature);
+}
+
+
+/// Replaces the tested population with the next one, or with random creatures
+/// if there is no population yet
+pub(crate) fn populate<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
+    generation: &mut EvolutionGeneration<F>,
+    populator: &mut GenerationPopulator,
 ) {
     if generation.population.is_empty() {
         let mut rng = rand::thread_rng();
-        for i in 0..populator.pop_size {
-            generation.population.push(populator.rand_params.build_morph(&mut rng, CreatureId(i)));
+        for _ in 0..populator.pop_size {
+            generation.population.push(populator.rand_params.build_morph(&mut rng, CreatureId(populator.current_id)));
             generation.populate_flags.push(CreaturePopulateFlag::Spawned);
             populator.current_id += 1;
         }
-        next_state.set(EvolutionState::EvaluatingCreature);
-        training_evw.send(EvolutionTrainingEvent::StartTestingGeneration(generation.current_generation));
+        if let Some(speciation) = populator.speciation.as_mut() {
+            generation.species = speciation.speciate(&generation.population);
+        }
         return;
     }
 
-    generation.current_generation += 1;
-
     let mut elite: Vec<_> = generation.population.iter().enumerate().collect();
     elite.sort_unstable_by(|(i, _), (j, _)| {
         (-generation.fitnesses[*i])
@@ -112,15 +210,77 @@ pub(crate) fn populate_generation<F: EvolutionFitnessEval + Send + Sync + Defaul
             .expect("Unable to sort generation, fitnesses likely contains NAN values")
     });
 
+    // Elites are retained even with MAP-Elites, which only breeds from its grid,
+    // so that islands have creatures to send each other
     let retained = (populator.elitism * populator.pop_size as f32).ceil() as usize;
     let rand_amt = (populator.rand_percent * populator.pop_size as f32).ceil() as usize;
 
     populator.best_fitness = generation.fitnesses[elite[0].0];
     populator.best_creature = elite[0].1.creature.0;
 
-    elite.truncate(retained);
+    if let Some(map_elites) = populator.map_elites.as_mut() {
+        if generation.traits.len() == generation.population.len() {
+            for ((creature, fitness), traits) in generation.population.iter().zip(generation.fitnesses.iter()).zip(generation.traits.iter())
+            {
+                map_elites.insert(creature, *fitness, *traits);
+            }
+        }
+    }
+
+    // Resumed sessions may be missing the behaviors of the last generation
+    let novelty = match populator.novelty.as_mut() {
+        Some(novelty) if generation.behaviors.len() == generation.population.len() => {
+            let scores = novelty.scores(&generation.behaviors);
+            novelty.update_archive(&generation.behaviors, &scores);
+            Some(scores)
+        },
+        _ => None,
+    };
+
+    // Sessions resumed from before objectives were saved only have fitnesses
+    if populator.selection == SelectionMethod::Nsga2 && generation.objectives.len() == generation.population.len() {
+        let objectives: Vec<Vec<f32>> = match &novelty {
+            Some(scores) => generation.objectives.iter().zip(scores).map(|(o, n)| o.iter().copied().chain([*n]).collect()).collect(),
+            None => generation.objectives.clone(),
+        };
+        elite = nsga2_order(&objectives).into_iter().map(|i| (i, &generation.population[i])).collect();
+    } else if let (Some(scores), Some(search)) = (&novelty, &populator.novelty) {
+        let blended: Vec<f32> = generation.fitnesses.iter().zip(scores).map(|(f, n)| search.blend(*f, *n)).collect();
+        elite.sort_by(|(i, _), (j, _)| blended[*j].total_cmp(&blended[*i]));
+    }
+
+    // Each parent is paired with how strongly its offspring is mutated, from 0
+    // to 1
+    let mutate_amt = populator.pop_size - retained - rand_amt;
+    let mut parents: Vec<(usize, f32)> =
+        (0..mutate_amt).map(|i| (i % retained.max(1), i as f32 / (mutate_amt.max(2) - 1) as f32)).collect();
+
+    // Resumed sessions may be missing the species of the last generation
+    let speciated = generation.species.len() == generation.population.len();
+    if populator.speciation.is_some() && populator.map_elites.is_none() && speciated {
+        let quotas = Speciation::quotas(&generation.species, &generation.fitnesses, populator.pop_size - rand_amt);
+        let mut kept = Vec::new();
+        parents.clear();
+        for (species, quota) in quotas {
+            if quota == 0 {
+                continue;
+            }
+            let members: Vec<_> = elite.iter().filter(|(i, _)| generation.species[*i] == species).copied().collect();
+            let keep = ((populator.elitism * quota as f32).ceil() as usize).clamp(1, quota).min(members.len());
+            let offspring = quota - keep;
+            parents.extend((0..offspring).map(|j| (kept.len() + j % keep, j as f32 / (offspring.max(2) - 1) as f32)));
+            kept.extend_from_slice(&members[..keep]);
+        }
+        elite = kept;
+    } else {
+        elite.truncate(retained);
+    }
+
     generation.population = elite.iter().map(|(_, x)| (*x).clone()).collect();
     generation.fitnesses.clear();
+    generation.objectives.clear();
+    generation.behaviors.clear();
+    generation.traits.clear();
 
     generation.populate_flags.clear();
     for _ in 0..generation.population.len() {
@@ -129,14 +289,19 @@ pub(crate) fn populate_generation<F: EvolutionFitnessEval + Send + Sync + Defaul
 
     let mut rng = rand::thread_rng();
     let mut params = populator.mutate_params.clone();
-    let mutate_amt = populator.pop_size - retained - rand_amt;
-    for i in 0..mutate_amt {
-        let mut morph = generation.population[i % retained].clone();
+    for (parent, strength) in parents {
+        let mut morph = match &populator.map_elites {
+            Some(map_elites) => match map_elites.random_elite(&mut rng) {
+                Some(elite) => elite.creature.clone(),
+                None => populator.rand_params.build_morph(&mut rng, CreatureId(populator.current_id)),
+            },
+            None => generation.population[parent].clone(),
+        };
         morph.creature = CreatureId(populator.current_id);
         populator.current_id += 1;
         let mut mutate = MutateMorphology::new(&mut morph, &mut rng, &mut params);
 
-        let n_mutations = (populator.num_mutations as f32 * (i as f32 / (mutate_amt - 1) as f32).powf(2.4)).ceil() as usize;
+        let n_mutations = (populator.num_mutations as f32 * strength.powf(2.4)).ceil() as usize;
         for _ in 0..n_mutations {
             mutate.mutate();
         }
@@ -151,6 +316,7 @@ pub(crate) fn populate_generation<F: EvolutionFitnessEval + Send + Sync + Defaul
         populator.current_id += 1;
     }
 
-    training_evw.send(EvolutionTrainingEvent::StartTestingGeneration(generation.current_generation));
-    next_state.set(EvolutionState::EvaluatingCreature);
+    if let Some(speciation) = populator.speciation.as_mut() {
+        generation.species = speciation.speciate(&generation.population);
+    }
 }
@@ -0,0 +1,99 @@
+use bevy::prelude::*;
+use bevy_rapier3d::{
+    dynamics::{RigidBody, Velocity},
+    geometry::{Collider, ColliderMassProperties, Friction},
+};
+use creature_builder::{sensor::ContactFilterTag, CreatureId};
+use serde::{Deserialize, Serialize};
+
+use super::terrain::Terrain;
+
+
+/// A block spawned near each creature when it is tested, for it to push towards
+/// a goal
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PushTask {
+    /// Where the block starts on the ground
+    pub start: Vec3,
+    /// Where the block should be pushed to
+    pub goal: Vec3,
+    /// Half the side length of the block
+    pub size: f32,
+    pub density: f32,
+    pub friction: f32,
+}
+
+impl Default for PushTask {
+    fn default() -> Self {
+        Self { start: Vec3::new(2.0, 0.0, 0.0), goal: Vec3::new(10.0, 0.0, 0.0), size: 0.5, density: 1.0, friction: 0.5 }
+    }
+}
+
+impl PushTask {
+    pub fn new(start: Vec3, goal: Vec3) -> Self {
+        Self { start, goal, ..Default::default() }
+    }
+
+    /// The same task turned to lie along `heading`, an (x, z) direction along
+    /// the ground, keeping the distances of the block and goal from the origin
+    pub fn with_heading(mut self, heading: Vec2) -> Self {
+        let heading = heading.try_normalize().unwrap_or(Vec2::X);
+        let direction = Vec3::new(heading.x, 0.0, heading.y);
+        self.start = direction * self.start.xz().length();
+        self.goal = direction * self.goal.xz().length();
+        self
+    }
+
+    /// Where the block starts, resting on the terrain if there is one
+    pub fn block_transform(&self, terrain: Option<&Terrain>) -> Transform {
+        let ground = terrain.map_or(0.0, |terrain| terrain.height_at(self.start.x, self.start.z));
+        Transform::from_translation(self.start + Vec3::Y * (ground + self.size))
+    }
+
+    /// Spawns the block for `creature` on the terrain, with a mesh if given the
+    /// assets to make one
+    pub fn spawn(
+        &self,
+        commands: &mut Commands,
+        creature: CreatureId,
+        terrain: Option<&Terrain>,
+        assets: Option<(&mut Assets<Mesh>, &mut Assets<StandardMaterial>)>,
+    ) -> Entity {
+        let transform = self.block_transform(terrain);
+        let block = commands
+            .spawn((
+                RigidBody::Dynamic,
+                Velocity::zero(),
+                Collider::cuboid(self.size, self.size, self.size),
+                ColliderMassProperties::Density(self.density),
+                Friction::coefficient(self.friction),
+                ContactFilterTag::ObjectGroup,
+                PushBlock { creature, goal: self.goal },
+                Name::new("Push Block"),
+            ))
+            .id();
+        match assets {
+            Some((meshes, materials)) => {
+                commands.entity(block).insert(PbrBundle {
+                    mesh: meshes.add(Mesh::from(shape::Cube::new(self.size * 2.0))),
+                    material: materials.add(StandardMaterial::from(Color::rgb(0.9, 0.6, 0.3))),
+                    transform,
+                    ..default()
+                });
+            },
+            None => {
+                commands.entity(block).insert((transform, GlobalTransform::default()));
+            },
+        }
+        block
+    }
+}
+
+
+/// The block of a [`PushTask`], which collides with limbs and the ground
+#[derive(Component, Clone, Copy, Debug)]
+pub struct PushBlock {
+    /// The creature the block was spawned for
+    pub creature: CreatureId,
+    pub goal: Vec3,
+}
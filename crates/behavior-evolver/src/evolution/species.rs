@@ -0,0 +1,240 @@
+use creature_builder::{
+    builder::node::{CreatureMorphologyGraph, LimbConnection, LimbNode},
+    effector::CreatureJointEffector,
+    expr::node::ExprNode,
+    neural::NeuralBrain,
+};
+use serde::{Deserialize, Serialize};
+
+
+/// The weights of each part of the genotype distance
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GenomeDistanceParams {
+    /// Per limb or connection present in only one of the creatures
+    pub structure: f32,
+    /// Per unit of difference between the parameters of matching limbs and
+    /// connections
+    pub parameters: f32,
+    /// Per edit between the effector expressions of matching connections
+    pub expressions: f32,
+}
+
+impl Default for GenomeDistanceParams {
+    fn default() -> Self {
+        Self { structure: 1.0, parameters: 0.5, expressions: 0.1 }
+    }
+}
+
+
+/// How different two morphologies are. Limbs and connections are matched by
+/// their ids, which mutation keeps from parent to child
+pub fn genome_distance(a: &CreatureMorphologyGraph, b: &CreatureMorphologyGraph, params: &GenomeDistanceParams) -> f32 {
+    let (a_nodes, b_nodes) = (a.nodes_map(), b.nodes_map());
+    let (a_edges, b_edges) = (a.edges_map(), b.edges_map());
+
+    let mut unmatched = 0;
+    let (mut parameters, mut expressions) = (0.0, 0.0);
+    for (id, node) in a_nodes.iter() {
+        match b_nodes.get(id) {
+            Some(other) => parameters += node_distance(&node.data, &other.data),
+            None => unmatched += 1,
+        }
+    }
+    unmatched += b_nodes.keys().filter(|id| !a_nodes.contains_key(id)).count();
+
+    for (id, edge) in a_edges.iter() {
+        match b_edges.get(id) {
+            Some(other) => {
+                parameters += connection_distance(&edge.data, &other.data);
+                expressions += effector_distance(&edge.data, &other.data);
+            },
+            None => unmatched += 1,
+        }
+    }
+    unmatched += b_edges.keys().filter(|id| !a_edges.contains_key(id)).count();
+
+    if let (Some(a_brain), Some(b_brain)) = (&a.brain, &b.brain) {
+        parameters += brain_distance(a_brain, b_brain);
+    }
+
+    let genes = (a_nodes.len() + a_edges.len()).max(b_nodes.len() + b_edges.len()).max(1) as f32;
+    params.structure * unmatched as f32 + (params.parameters * parameters + params.expressions * expressions) / genes
+}
+
+
+fn node_distance(a: &LimbNode, b: &LimbNode) -> f32 {
+    (a.density - b.density).abs()
+        + (a.friction - b.friction).abs()
+        + (a.restitution - b.restitution).abs()
+        + a.recursive_limit.abs_diff(b.recursive_limit) as f32
+        + if a.terminal_only != b.terminal_only { 1.0 } else { 0.0 }
+}
+
+
+fn connection_distance(a: &LimbConnection, b: &LimbConnection) -> f32 {
+    let (a_place, b_place) = (&a.placement, &b.placement);
+    let limits: f32 =
+        a.limit_axes.iter().flatten().zip(b.limit_axes.iter().flatten()).map(|(a, b)| (a - b).abs()).filter(|d| d.is_finite()).sum();
+
+    (a_place.scale - b_place.scale).length()
+        + (a_place.attach_position - b_place.attach_position).length()
+        + a_place.orientation.angle_between(b_place.orientation)
+        + if a_place.attach_face != b_place.attach_face { 1.0 } else { 0.0 }
+        + if a.locked_axes != b.locked_axes { 1.0 } else { 0.0 }
+        + limits
+}
+
+
+fn effector_distance(a: &LimbConnection, b: &LimbConnection) -> f32 {
+    let size = |effector: &Option<CreatureJointEffector>| effector.as_ref().map_or(0, |e| expr_size(&e.expr.root)) as f32;
+    a.effectors
+        .effectors
+        .iter()
+        .zip(b.effectors.effectors.iter())
+        .map(|(a_effector, b_effector)| match (a_effector, b_effector) {
+            (Some(a), Some(b)) => expr_distance(&a.expr.root, &b.expr.root),
+            _ => size(a_effector) + size(b_effector),
+        })
+        .sum()
+}
+
+
+fn brain_distance(a: &NeuralBrain, b: &NeuralBrain) -> f32 {
+    let weights = |brain: &NeuralBrain| {
+        brain
+            .hidden_weights
+            .iter()
+            .flatten()
+            .chain(brain.hidden_biases.iter())
+            .chain(brain.output_weights.iter().flatten())
+            .chain(brain.output_biases.iter())
+            .copied()
+            .collect::<Vec<f32>>()
+    };
+    let (a, b) = (weights(a), weights(b));
+    let shared = a.len().min(b.len());
+    a.iter().zip(b.iter()).map(|(a, b)| (a - b).abs()).sum::<f32>() / shared.max(1) as f32 + a.len().abs_diff(b.len()) as f32
+}
+
+
+/// The number of nodes in an expression tree
+pub fn expr_size(node: &ExprNode) -> usize {
+    match node {
+        ExprNode::Value(_) | ExprNode::Constant(_) => 1,
+        ExprNode::UnaryOp(_, a) => 1 + expr_size(a),
+        ExprNode::BinaryOp(_, a, b) => 1 + expr_size(a) + expr_size(b),
+        ExprNode::TernaryOp(_, a, b, c) => 1 + expr_size(a) + expr_size(b) + expr_size(c),
+    }
+}
+
+
+/// A top-down tree edit distance, where nodes of the same kind are compared
+/// child by child and any other pair of subtrees is replaced outright
+pub fn expr_distance(a: &ExprNode, b: &ExprNode) -> f32 {
+    let op = |same: bool| if same { 0.0 } else { 1.0 };
+    match (a, b) {
+        (ExprNode::Value(a), ExprNode::Value(b)) => op(a == b),
+        (ExprNode::Constant(a), ExprNode::Constant(b)) => (a.0 - b.0).abs().min(1.0),
+        (ExprNode::UnaryOp(a_op, a), ExprNode::UnaryOp(b_op, b)) => op(a_op == b_op) + expr_distance(a, b),
+        (ExprNode::BinaryOp(a_op, a1, a2), ExprNode::BinaryOp(b_op, b1, b2)) => {
+            op(a_op == b_op) + expr_distance(a1, b1) + expr_distance(a2, b2)
+        },
+        (ExprNode::TernaryOp(a_op, a1, a2, a3), ExprNode::TernaryOp(b_op, b1, b2, b3)) => {
+            op(a_op == b_op) + expr_distance(a1, b1) + expr_distance(a2, b2) + expr_distance(a3, b3)
+        },
+        _ => expr_size(a).max(expr_size(b)) as f32,
+    }
+}
+
+
+/// Groups creatures with similar genotypes into species, which compete mostly
+/// among themselves so that new structures have time to improve
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Speciation {
+    /// The largest genotype distance from a species' representative for a
+    /// creature to join it
+    pub threshold: f32,
+    pub distance: GenomeDistanceParams,
+    /// A member of each species from the last generation, with its species id
+    pub representatives: Vec<(usize, CreatureMorphologyGraph)>,
+    pub next_species: usize,
+}
+
+impl Default for Speciation {
+    fn default() -> Self {
+        Self { threshold: 3.0, distance: GenomeDistanceParams::default(), representatives: Vec::new(), next_species: 0 }
+    }
+}
+
+impl Speciation {
+    pub fn new(threshold: f32) -> Self {
+        Self { threshold, ..Default::default() }
+    }
+
+    /// Assigns each creature to the first species whose representative is close
+    /// enough, founding new species for the rest, and returns the species id of
+    /// each creature
+    pub fn speciate(&mut self, population: &[CreatureMorphologyGraph]) -> Vec<usize> {
+        let mut species = Vec::with_capacity(population.len());
+        let mut representatives = self.representatives.clone();
+        for creature in population.iter() {
+            let found = representatives
+                .iter()
+                .find(|(_, representative)| genome_distance(creature, representative, &self.distance) < self.threshold)
+                .map(|(id, _)| *id);
+            let id = match found {
+                Some(id) => id,
+                None => {
+                    let id = self.next_species;
+                    self.next_species += 1;
+                    representatives.push((id, creature.clone()));
+                    id
+                },
+            };
+            species.push(id);
+        }
+
+        // Each remaining species is represented by its first member from this
+        // generation
+        self.representatives = representatives
+            .into_iter()
+            .filter_map(|(id, _)| species.iter().position(|s| *s == id).map(|i| (id, population[i].clone())))
+            .collect();
+        species
+    }
+
+    /// Divides `slots` offspring between species in proportion to their total
+    /// shared fitness, where fitness is shifted to be non-negative and divided
+    /// by the size of the species. Returned in order of first appearance
+    pub fn quotas(species: &[usize], fitnesses: &[f32], slots: usize) -> Vec<(usize, usize)> {
+        let min = fitnesses.iter().copied().fold(f32::INFINITY, f32::min);
+        let mut totals: Vec<(usize, f32, usize)> = Vec::new();
+        for (id, fitness) in species.iter().zip(fitnesses) {
+            match totals.iter_mut().find(|(s, _, _)| s == id) {
+                Some((_, total, size)) => {
+                    *total += fitness - min;
+                    *size += 1;
+                },
+                None => totals.push((*id, fitness - min, 1)),
+            }
+        }
+        let shared: Vec<f32> = totals.iter().map(|(_, total, size)| total / *size as f32).collect();
+        let sum: f32 = shared.iter().sum();
+
+        // Largest remainder, falling back to equal shares when every creature
+        // has the same fitness
+        let exact: Vec<f32> = shared
+            .iter()
+            .map(|s| if sum > 0.0 && sum.is_finite() { s / sum * slots as f32 } else { slots as f32 / shared.len() as f32 })
+            .collect();
+        let mut quotas: Vec<usize> = exact.iter().map(|e| e.floor() as usize).collect();
+        let mut order: Vec<usize> = (0..exact.len()).collect();
+        order.sort_by(|a, b| (exact[*b] - exact[*b].floor()).total_cmp(&(exact[*a] - exact[*a].floor())));
+        let remaining = slots.saturating_sub(quotas.iter().sum());
+        for i in order.into_iter().cycle().take(remaining) {
+            quotas[i] += 1;
+        }
+
+        totals.iter().zip(quotas).map(|((id, _, _), quota)| (*id, quota)).collect()
+    }
+}
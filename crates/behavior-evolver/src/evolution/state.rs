@@ -1,10 +1,14 @@
 use bevy::prelude::*;
+use creature_builder::config::CreatureBuilderConfig;
 
 use super::{
+    curriculum::Curriculum,
     fitness::EvolutionFitnessEval,
     generation::{EvolutionGeneration, GenerationTestingConfig},
+    island::IslandModel,
     populate::GenerationPopulator,
-    write::load_session,
+    write::{load_session, write_environment},
+    EnvironmentConfig,
 };
 
 
@@ -27,12 +31,25 @@ pub enum EvolutionTrainingEvent {
 }
 
 
+#[allow(clippy::too_many_arguments)]
 pub fn begin_training_session<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
     mut next_state: ResMut<NextState<EvolutionState>>,
     mut generation: ResMut<EvolutionGeneration<F>>,
     mut populator: ResMut<GenerationPopulator>,
-    gen_test_conf: Res<GenerationTestingConfig>,
+    mut islands: Option<ResMut<IslandModel>>,
+    mut gen_test_conf: ResMut<GenerationTestingConfig>,
+    environment: Res<EnvironmentConfig>,
+    mut curriculum: ResMut<Curriculum>,
+    mut build_conf: ResMut<CreatureBuilderConfig>,
 ) {
-    load_session(generation.as_mut(), populator.as_mut(), gen_test_conf.as_ref());
+    write_environment(&gen_test_conf.session, environment.as_ref());
+    load_session(
+        generation.as_mut(),
+        populator.as_mut(),
+        islands.as_deref_mut(),
+        gen_test_conf.as_mut(),
+        curriculum.as_mut(),
+        build_conf.as_mut(),
+    );
     next_state.set(EvolutionState::PopulatingGeneration);
 }
@@ -0,0 +1,245 @@
+use bevy::{
+    prelude::*,
+    render::{mesh::Indices, render_resource::PrimitiveTopology},
+};
+use bevy_rapier3d::geometry::Collider;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+
+/// The shape of the ground creatures are tested on. Every terrain is built so
+/// that the ground around the origin, where creatures are spawned, is at a
+/// height of zero
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Terrain {
+    #[default]
+    Flat,
+    /// Rolling hills generated from seeded value noise
+    Heightfield { seed: u64, size: f32, resolution: usize, amplitude: f32, feature_size: f32 },
+    /// A staircase climbing along +x
+    Stairs { step_height: f32, step_depth: f32, steps: usize },
+    /// A ramp climbing along +x starting at `start`, with `angle` in radians
+    Slope { angle: f32, start: f32 },
+    /// Randomly sized and placed boxes scattered over flat ground, keeping the
+    /// spawn area clear
+    Boxes { seed: u64, count: usize, min_size: f32, max_size: f32, spread: f32 },
+    /// Platforms along +x separated by gaps, the first of which is centered on
+    /// the origin
+    Gaps { gap_width: f32, platform_length: f32, platforms: usize },
+}
+
+
+#[derive(Clone, Debug)]
+pub enum TerrainShape {
+    Cuboid(Vec3),
+    /// Column-major heights, with rows along z and columns along x
+    Heightfield {
+        heights: Vec<f32>,
+        rows: usize,
+        cols: usize,
+        scale: Vec3,
+    },
+}
+
+impl TerrainShape {
+    pub fn collider(&self) -> Collider {
+        match self {
+            Self::Cuboid(half_size) => Collider::cuboid(half_size.x, half_size.y, half_size.z),
+            Self::Heightfield { heights, rows, cols, scale } => Collider::heightfield(heights.clone(), *rows, *cols, *scale),
+        }
+    }
+
+    pub fn mesh(&self) -> Mesh {
+        match self {
+            Self::Cuboid(half_size) => Mesh::from(shape::Box::new(half_size.x * 2.0, half_size.y * 2.0, half_size.z * 2.0)),
+            Self::Heightfield { heights, rows, cols, scale } => {
+                let mut positions = Vec::with_capacity(heights.len());
+                for col in 0..*cols {
+                    for row in 0..*rows {
+                        let x = (col as f32 / (*cols - 1) as f32 - 0.5) * scale.x;
+                        let z = (row as f32 / (*rows - 1) as f32 - 0.5) * scale.z;
+                        positions.push([x, heights[row + col * rows] * scale.y, z]);
+                    }
+                }
+
+                let mut indices = Vec::with_capacity((rows - 1) * (cols - 1) * 6);
+                for col in 0..(cols - 1) as u32 {
+                    for row in 0..(rows - 1) as u32 {
+                        let i = row + col * *rows as u32;
+                        let right = i + *rows as u32;
+                        indices.extend_from_slice(&[i, i + 1, right, right, i + 1, right + 1]);
+                    }
+                }
+
+                let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+                mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+                mesh.set_indices(Some(Indices::U32(indices)));
+                mesh.duplicate_vertices();
+                mesh.compute_flat_normals();
+                mesh
+            },
+        }
+    }
+}
+
+
+#[derive(Clone, Debug)]
+pub struct TerrainPiece {
+    pub shape: TerrainShape,
+    pub transform: Transform,
+}
+
+impl TerrainPiece {
+    fn cuboid(half_size: Vec3, transform: Transform) -> Self {
+        Self { shape: TerrainShape::Cuboid(half_size), transform }
+    }
+
+    fn flat() -> Self {
+        Self::cuboid(Vec3::new(500.0, 5.0, 500.0), Transform::from_xyz(0.0, -5.0, 0.0))
+    }
+}
+
+
+impl Terrain {
+    /// A terrain of the given kind with default parameters
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "flat" => Some(Self::Flat),
+            "hills" => Some(Self::Heightfield { seed: 0, size: 200.0, resolution: 128, amplitude: 3.0, feature_size: 10.0 }),
+            "stairs" => Some(Self::Stairs { step_height: 0.25, step_depth: 2.0, steps: 10 }),
+            "slope" => Some(Self::Slope { angle: 0.2, start: 3.0 }),
+            "boxes" => Some(Self::Boxes { seed: 0, count: 100, min_size: 0.25, max_size: 1.5, spread: 40.0 }),
+            "gaps" => Some(Self::Gaps { gap_width: 1.0, platform_length: 8.0, platforms: 10 }),
+            _ => None,
+        }
+    }
+
+    /// The same terrain generated from a seed shifted by `offset`, or an
+    /// unchanged copy of terrains that aren't random
+    pub fn reseeded(&self, offset: u64) -> Self {
+        let mut terrain = self.clone();
+        match &mut terrain {
+            Self::Heightfield { seed, .. } | Self::Boxes { seed, .. } => *seed = seed.wrapping_add(offset),
+            _ => (),
+        }
+        terrain
+    }
+
+    /// The height of the top of the terrain at the given point on the ground,
+    /// or zero over a gap
+    pub fn height_at(&self, x: f32, z: f32) -> f32 {
+        let top = 1000.0;
+        self.pieces()
+            .iter()
+            .filter_map(|piece| {
+                let collider = piece.shape.collider();
+                let transform = piece.transform;
+                collider.cast_ray(transform.translation, transform.rotation, Vec3::new(x, top, z), Vec3::NEG_Y, f32::MAX, true)
+            })
+            .map(|toi| top - toi)
+            .reduce(f32::max)
+            .unwrap_or(0.0)
+    }
+
+    /// The static bodies that make up the terrain
+    pub fn pieces(&self) -> Vec<TerrainPiece> {
+        match self {
+            Self::Flat => vec![TerrainPiece::flat()],
+            Self::Heightfield { seed, size, resolution, amplitude, feature_size } => {
+                let n = (*resolution).max(1) + 1;
+                let mut rng = StdRng::seed_from_u64(*seed);
+                let mut heights = value_noise(&mut rng, n, *size / feature_size.max(f32::EPSILON));
+                let center = heights[n / 2 + (n / 2) * n];
+                heights.iter_mut().for_each(|h| *h = (*h - center) * amplitude);
+
+                vec![TerrainPiece {
+                    shape: TerrainShape::Heightfield { heights, rows: n, cols: n, scale: Vec3::new(*size, 1.0, *size) },
+                    transform: Transform::IDENTITY,
+                }]
+            },
+            Self::Stairs { step_height, step_depth, steps } => {
+                let start = 2.0;
+                let end = start + *steps as f32 * step_depth + 50.0;
+                let mut pieces = vec![TerrainPiece::flat()];
+                for i in 0..*steps {
+                    let begin = start + i as f32 * step_depth;
+                    let height = (i + 1) as f32 * step_height;
+                    pieces.push(TerrainPiece::cuboid(
+                        Vec3::new((end - begin) / 2.0, height / 2.0, 25.0),
+                        Transform::from_xyz((begin + end) / 2.0, height / 2.0, 0.0),
+                    ));
+                }
+                pieces
+            },
+            Self::Slope { angle, start } => {
+                let half_size = Vec3::new(50.0, 0.5, 25.0);
+                let rotation = Quat::from_rotation_z(*angle);
+                let center = Vec3::new(*start, 0.0, 0.0) + rotation * Vec3::new(half_size.x, -half_size.y, 0.0);
+                vec![TerrainPiece::flat(), TerrainPiece::cuboid(half_size, Transform::from_translation(center).with_rotation(rotation))]
+            },
+            Self::Boxes { seed, count, min_size, max_size, spread } => {
+                let mut rng = StdRng::seed_from_u64(*seed);
+                let mut pieces = vec![TerrainPiece::flat()];
+                let clear_radius = 3.0 + max_size;
+                while pieces.len() <= *count && *spread > clear_radius {
+                    let position = Vec2::new(rng.gen_range(-*spread..*spread), rng.gen_range(-*spread..*spread));
+                    if position.length() < clear_radius {
+                        continue;
+                    }
+                    let half_size = Vec3::new(
+                        rng.gen_range(*min_size..=*max_size),
+                        rng.gen_range(*min_size..=*max_size),
+                        rng.gen_range(*min_size..=*max_size),
+                    ) / 2.0;
+                    let transform = Transform::from_xyz(position.x, half_size.y, position.y)
+                        .with_rotation(Quat::from_rotation_y(rng.gen_range(0.0..std::f32::consts::TAU)));
+                    pieces.push(TerrainPiece::cuboid(half_size, transform));
+                }
+                pieces
+            },
+            Self::Gaps { gap_width, platform_length, platforms } => (0..*platforms)
+                .map(|i| {
+                    TerrainPiece::cuboid(
+                        Vec3::new(platform_length / 2.0, 5.0, 25.0),
+                        Transform::from_xyz(i as f32 * (platform_length + gap_width), -5.0, 0.0),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+
+/// Generates an `n`x`n` grid of smooth noise in the range [0, 1], where `cells`
+/// is the number of noise features across the grid
+fn value_noise(rng: &mut StdRng, n: usize, cells: f32) -> Vec<f32> {
+    let mut heights = vec![0.0; n * n];
+    let mut total_weight = 0.0;
+
+    // Sum a few octaves, each with twice the detail and half the weight
+    for octave in 0..4 {
+        let lattice_cells = (cells * 2f32.powi(octave)).ceil().max(1.0) as usize;
+        let lattice_size = lattice_cells + 1;
+        let lattice: Vec<f32> = (0..lattice_size * lattice_size).map(|_| rng.gen()).collect();
+        let weight = 0.5f32.powi(octave);
+        total_weight += weight;
+
+        for col in 0..n {
+            for row in 0..n {
+                let x = col as f32 / (n - 1).max(1) as f32 * lattice_cells as f32;
+                let z = row as f32 / (n - 1).max(1) as f32 * lattice_cells as f32;
+                let (x0, z0) = ((x as usize).min(lattice_cells - 1), (z as usize).min(lattice_cells - 1));
+                let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+                let (tx, tz) = (smooth(x - x0 as f32), smooth(z - z0 as f32));
+
+                let at = |x: usize, z: usize| lattice[z + x * lattice_size];
+                let near = at(x0, z0) + (at(x0 + 1, z0) - at(x0, z0)) * tx;
+                let far = at(x0, z0 + 1) + (at(x0 + 1, z0 + 1) - at(x0, z0 + 1)) * tx;
+                heights[row + col * n] += (near + (far - near) * tz) * weight;
+            }
+        }
+    }
+
+    heights.iter_mut().for_each(|h| *h /= total_weight);
+    heights
+}
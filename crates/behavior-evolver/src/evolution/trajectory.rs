@@ -0,0 +1,187 @@
+use bevy::{
+    math::{Quat, Vec3, Vec3Swizzles},
+    prelude::Resource,
+};
+
+use super::fitness::FitnessEvalInput;
+
+
+/// The state of a creature at one recorded step of its test
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrajectoryStep {
+    pub step: usize,
+    pub center: Vec3,
+    pub root_rotation: Quat,
+    /// The height of the lowest point of the creature
+    pub height: f32,
+    /// The portion of the limbs touching the ground
+    pub contact: f32,
+    /// The speed of the center of mass
+    pub speed: f32,
+    /// The energy spent so far
+    pub work: f32,
+}
+
+impl TrajectoryStep {
+    pub fn from_input(input: &FitnessEvalInput) -> Self {
+        Self {
+            step: input.step,
+            center: input.center_of_mass(),
+            root_rotation: input.limbs.get(input.root).map_or(Quat::IDENTITY, |(transform, _)| transform.rotation),
+            height: input.lowest_point(),
+            contact: input.ground_contact_ratio(),
+            speed: input.center_velocity().length(),
+            work: input.energy.work,
+        }
+    }
+}
+
+
+/// The recorded steps of one trial of a creature, and the score it got
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trajectory {
+    pub creature: usize,
+    pub trial: usize,
+    pub fitness: f32,
+    pub steps: Vec<TrajectoryStep>,
+}
+
+/// What a trajectory shows about how a creature moved
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TrajectorySummary {
+    /// How far the center of mass moved along the ground
+    pub displacement: f32,
+    /// The greatest height the lowest point of the creature reached
+    pub max_height: f32,
+    pub mean_contact: f32,
+    pub mean_speed: f32,
+    /// The energy spent over the whole trial
+    pub work: f32,
+    /// How far the root limb turned about the vertical, in radians
+    pub turn: f32,
+}
+
+impl Trajectory {
+    pub const CSV_HEADER: &'static str = "creature,trial,fitness,step,x,y,z,qx,qy,qz,qw,height,contact,speed,work";
+
+    pub fn summary(&self) -> TrajectorySummary {
+        let (Some(first), Some(last)) = (self.steps.first(), self.steps.last()) else { return TrajectorySummary::default() };
+        let n = self.steps.len() as f32;
+        let facing = (last.root_rotation * first.root_rotation.inverse() * Vec3::X).xz();
+        TrajectorySummary {
+            displacement: (last.center - first.center).xz().length(),
+            max_height: self.steps.iter().map(|step| step.height).fold(f32::MIN, f32::max),
+            mean_contact: self.steps.iter().map(|step| step.contact).sum::<f32>() / n,
+            mean_speed: self.steps.iter().map(|step| step.speed).sum::<f32>() / n,
+            work: last.work,
+            turn: facing.y.atan2(facing.x).abs(),
+        }
+    }
+
+    /// The trajectories as CSV with one row per step, for plotting
+    pub fn to_csv(trajectories: &[Self]) -> String {
+        let mut csv = format!("{}\n", Self::CSV_HEADER);
+        for trajectory in trajectories.iter() {
+            for step in trajectory.steps.iter() {
+                let (c, q) = (step.center, step.root_rotation);
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+                    trajectory.creature,
+                    trajectory.trial,
+                    trajectory.fitness,
+                    step.step,
+                    c.x,
+                    c.y,
+                    c.z,
+                    q.x,
+                    q.y,
+                    q.z,
+                    q.w,
+                    step.height,
+                    step.contact,
+                    step.speed,
+                    step.work
+                ));
+            }
+        }
+        csv
+    }
+
+    /// Parses the trajectories written by `to_csv`, returning `None` if any row
+    /// is malformed
+    pub fn from_csv(csv: &str) -> Option<Vec<Self>> {
+        let mut trajectories: Vec<Self> = Vec::new();
+        for line in csv.lines().skip(1).filter(|line| !line.is_empty()) {
+            let values: Vec<&str> = line.split(',').collect();
+            if values.len() != 15 {
+                return None;
+            }
+            let float = |i: usize| values[i].parse::<f32>().ok();
+            let (creature, trial) = (values[0].parse().ok()?, values[1].parse().ok()?);
+            let step = TrajectoryStep {
+                step: values[3].parse().ok()?,
+                center: Vec3::new(float(4)?, float(5)?, float(6)?),
+                root_rotation: Quat::from_xyzw(float(7)?, float(8)?, float(9)?, float(10)?),
+                height: float(11)?,
+                contact: float(12)?,
+                speed: float(13)?,
+                work: float(14)?,
+            };
+            match trajectories.last_mut() {
+                Some(last) if last.creature == creature && last.trial == trial => last.steps.push(step),
+                _ => trajectories.push(Self { creature, trial, fitness: float(2)?, steps: vec![step] }),
+            }
+        }
+        Some(trajectories)
+    }
+}
+
+
+/// Records the trajectory of every creature tested, written to the session with
+/// each generation. Creatures tested one at a time are recorded on the first
+/// side, and the two creatures of a match on a side each
+#[derive(Resource, Clone, Debug)]
+pub struct TrajectoryRecorder {
+    /// The number of steps between samples
+    pub interval: usize,
+    current: [Vec<TrajectoryStep>; 2],
+    finished: Vec<Trajectory>,
+}
+
+impl TrajectoryRecorder {
+    pub fn new(interval: usize) -> Self {
+        Self { interval, current: Default::default(), finished: Vec::new() }
+    }
+
+    /// Samples the state of the creature being tested if a sample is due
+    pub fn record(&mut self, input: &FitnessEvalInput) {
+        self.record_side(0, input);
+    }
+
+    /// Samples the state of the creature on one side of a match if a sample is
+    /// due
+    pub fn record_side(&mut self, side: usize, input: &FitnessEvalInput) {
+        if input.step % self.interval.max(1) == 0 {
+            self.current[side].push(TrajectoryStep::from_input(input));
+        }
+    }
+
+    /// Files the samples taken since the last trial ended under the trial that
+    /// just ended
+    pub fn finish_trial(&mut self, creature: usize, trial: usize, fitness: f32) {
+        self.finish_side(0, creature, trial, fitness);
+    }
+
+    /// Files the samples of one side of a match under the match that just
+    /// ended, counted as a trial of the creature
+    pub fn finish_side(&mut self, side: usize, creature: usize, trial: usize, fitness: f32) {
+        let steps = std::mem::take(&mut self.current[side]);
+        self.finished.push(Trajectory { creature, trial, fitness, steps });
+    }
+
+    /// The trajectories of the trials that ended since the last call
+    pub fn take(&mut self) -> Vec<Trajectory> {
+        self.current.iter_mut().for_each(Vec::clear);
+        std::mem::take(&mut self.finished)
+    }
+}
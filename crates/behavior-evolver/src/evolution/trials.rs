@@ -0,0 +1,96 @@
+use std::f32::consts::TAU;
+
+use bevy::math::Vec3;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+
+/// How the scores of a creature's trials are combined into its fitness
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum TrialAggregation {
+    #[default]
+    Mean,
+    /// The score of the worst trial
+    Min,
+    Median,
+    /// The mean less this many standard deviations, which favors creatures that
+    /// score consistently
+    MeanMinusStdDev(f32),
+}
+
+impl TrialAggregation {
+    /// Parses `mean`, `min`, `median` or `std:<K>` for the mean less `K`
+    /// standard deviations
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "mean" => Some(Self::Mean),
+            "min" => Some(Self::Min),
+            "median" => Some(Self::Median),
+            _ => name.strip_prefix("std:").and_then(|k| k.parse().ok()).map(Self::MeanMinusStdDev),
+        }
+    }
+
+    pub fn aggregate(&self, scores: &[f32]) -> f32 {
+        if scores.is_empty() {
+            return 0.0;
+        }
+        let n = scores.len() as f32;
+        let mean = scores.iter().sum::<f32>() / n;
+        match self {
+            Self::Mean => mean,
+            Self::Min => scores.iter().copied().fold(f32::INFINITY, f32::min),
+            Self::Median => {
+                let mut sorted = scores.to_vec();
+                sorted.sort_by(f32::total_cmp);
+                let mid = sorted.len() / 2;
+                if sorted.len() % 2 == 0 {
+                    (sorted[mid - 1] + sorted[mid]) / 2.0
+                } else {
+                    sorted[mid]
+                }
+            },
+            Self::MeanMinusStdDev(k) => {
+                let variance = scores.iter().map(|score| (score - mean).powi(2)).sum::<f32>() / n;
+                mean - k * variance.sqrt()
+            },
+        }
+    }
+}
+
+
+/// Random changes to how a creature is spawned in each of its trials
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TrialPerturbation {
+    /// The most the creature is turned either way about the vertical axis, in
+    /// radians
+    pub max_yaw: f32,
+    /// The furthest the creature is moved along the ground
+    pub max_offset: f32,
+    /// Generates random terrains from a different seed in every trial after the
+    /// first
+    pub reseed_terrain: bool,
+}
+
+impl TrialPerturbation {
+    pub fn new(max_yaw: f32, max_offset: f32) -> Self {
+        Self { max_yaw, max_offset, reseed_terrain: false }
+    }
+
+    pub fn with_terrain_reseeding(mut self) -> Self {
+        self.reseed_terrain = true;
+        self
+    }
+
+    /// A random offset along the ground and turn to apply to a creature
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> (Vec3, f32) {
+        let turn = if self.max_yaw > 0.0 { rng.gen_range(-self.max_yaw..=self.max_yaw) } else { 0.0 };
+        let offset = if self.max_offset > 0.0 {
+            // Uniform over the disc rather than bunched up at its center
+            let (angle, radius) = (rng.gen_range(0.0..TAU), self.max_offset * rng.gen::<f32>().sqrt());
+            Vec3::new(angle.cos() * radius, 0.0, angle.sin() * radius)
+        } else {
+            Vec3::ZERO
+        };
+        (offset, turn)
+    }
+}
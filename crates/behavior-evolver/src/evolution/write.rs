@@ -1,13 +1,28 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use bevy::prelude::*;
-use creature_builder::builder::node::CreatureMorphologyGraph;
+use creature_builder::{builder::node::CreatureMorphologyGraph, config::CreatureBuilderConfig};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use super::{
-    fitness::EvolutionFitnessEval,
+    curriculum::Curriculum,
+    diagnostics::PhysicsDiagnostics,
+    fitness::{EvolutionFitnessEval, PENALTY_FITNESS},
+    fluid::FluidConfig,
     generation::{EvolutionGeneration, GenerationTestingConfig},
-    populate::GenerationPopulator,
+    hall_of_fame::{HallOfFame, HallOfFameEntry},
+    island::IslandModel,
+    map_elites::{CreatureTraits, MapElites},
+    novelty::BehaviorDescriptor,
+    nsga::non_dominated_sort,
+    populate::{GenerationPopulator, SelectionMethod},
     state::EvolutionState,
+    terrain::Terrain,
+    trajectory::{Trajectory, TrajectoryRecorder},
+    EnvironmentConfig,
 };
 use crate::evolution::populate::CreaturePopulateFlag;
 
@@ -15,6 +30,9 @@ use crate::evolution::populate::CreaturePopulateFlag;
 pub struct TrainingPaths {
     pub session: PathBuf,
     pub creatures: PathBuf,
+    /// Holds the creatures of the hall of fame, which are kept after they
+    /// leave the population
+    pub hall_of_fame: PathBuf,
 }
 
 
@@ -41,7 +59,7 @@ pub fn data_path() -> PathBuf {
 pub fn train_path(session: &str) -> TrainingPaths {
     let get_dir = |target: &PathBuf| -> TrainingPaths {
         let sess = target.join(session);
-        TrainingPaths { creatures: sess.join("creatures/"), session: sess }
+        TrainingPaths { creatures: sess.join("creatures/"), hall_of_fame: sess.join("hall-of-fame/"), session: sess }
     };
     let create_dir = |target: &PathBuf| {
         let paths = get_dir(target);
@@ -49,6 +67,7 @@ pub fn train_path(session: &str) -> TrainingPaths {
         if !paths.session.exists() {
             fs::create_dir(paths.session).expect("Unable to create session directory");
             fs::create_dir(paths.creatures).expect("Unable to create creature directory");
+            fs::create_dir(paths.hall_of_fame).expect("Unable to create hall of fame directory");
         };
     };
 
@@ -85,37 +104,264 @@ pub fn load_list(list_name: &str) -> Vec<CreatureMorphologyGraph> {
     creatures
 }
 
+/// Loads a creature from the last generation, or from the hall of fame if it
+/// has since left the population
 pub fn load_creature(session: &str, id: usize) -> CreatureMorphologyGraph {
-    let path = train_path(session).creatures.join(format!("id-{}.ron", id));
+    let train_dir = train_path(session);
+    let mut path = train_dir.creatures.join(format!("id-{}.ron", id));
+    if !path.exists() {
+        path = train_dir.hall_of_fame.join(format!("id-{}.ron", id));
+    }
     let creature_data = fs::read_to_string(path).expect("Unable to read existing creature data file");
     let creature_de: CreatureMorphologyGraph = ron::de::from_str(&creature_data).expect("Unable to parse creature data");
     creature_de
 }
 
+/// One creature of a generation file, written as a RON struct on its own line.
+/// Fields added since the first generation files were written are optional
+#[derive(Serialize, Deserialize)]
+struct CreatureRecord {
+    id: usize,
+    fitness: f32,
+    flags: CreaturePopulateFlag,
+    #[serde(default)]
+    objectives: Option<Vec<f32>>,
+    #[serde(default)]
+    behavior: Option<BehaviorDescriptor>,
+    #[serde(default)]
+    traits: Option<CreatureTraits>,
+    #[serde(default)]
+    species: Option<usize>,
+    #[serde(default)]
+    island: Option<usize>,
+    #[serde(default)]
+    trials: Option<Vec<f32>>,
+    #[serde(default)]
+    diagnostics: Option<PhysicsDiagnostics>,
+}
+
+/// One creature of the hall of fame file, whose morphology is kept in the hall
+/// of fame directory
+#[derive(Serialize, Deserialize)]
+struct HallOfFameRecord {
+    id: usize,
+    fitness: f32,
+    generation: usize,
+}
+
+/// Reads the records of a generation or hall of fame file, which follow a
+/// header line and a blank line. Lines in the older `key: [value]  key: [value]`
+/// format are rewritten as RON structs before parsing
+fn read_records<T: DeserializeOwned>(data: &str, file: &str) -> Vec<T> {
+    let legacy = |line: &str| -> String {
+        let fields: Vec<String> = line
+            .split("  ")
+            .filter_map(|field| {
+                let (key, value) = field.split_once(": ")?;
+                Some(format!("{}: {}", key, value.strip_prefix('[')?.strip_suffix(']')?))
+            })
+            .collect();
+        format!("({})", fields.join(", "))
+    };
+    data.lines()
+        .skip(2)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            ron::de::from_str(line)
+                .or_else(|_| ron::de::from_str(&legacy(line)))
+                .unwrap_or_else(|e| panic!("Failed to parse a creature in {}: {}", file, e))
+        })
+        .collect()
+}
+
+fn read_generation_records(train_dir: &TrainingPaths) -> Vec<CreatureRecord> {
+    let gen_data = fs::read_to_string(train_dir.session.join("last-gen.dat")).expect("Unable to read existing generation file");
+    read_records(&gen_data, "last-gen.dat")
+}
+
 pub fn load_generation(session: &str) -> Vec<CreatureMorphologyGraph> {
     let train_dir = train_path(session);
-    let gen_data = fs::read_to_string(train_dir.session.join("last-gen.dat")).expect("Unable to read existing generation file");
-    let mut population = Vec::new();
+    read_generation_records(&train_dir).iter().map(|record| load_creature(session, record.id)).collect()
+}
 
-    for line in gen_data[..gen_data.len() - 1].split('\n').skip(2) {
-        let mut elements = line.split("  ");
-        let mut grab_value = |offset: usize| {
-            let text = &elements.next().expect("Invalid generation file")[offset..];
-            &text[..text.len() - 1]
-        };
+/// Saves the environment of a session so that playback can recreate it
+pub fn write_environment(session: &str, environment: &EnvironmentConfig) {
+    let path = train_path(session).session.join("environment.ron");
+    let serialized = ron::ser::to_string_pretty(environment, ron::ser::PrettyConfig::default()).unwrap();
+    fs::write(path, serialized).expect("Failed to write environment file");
+}
 
-        let id: usize = grab_value(5).parse().expect("Failed to parse id in generation file");
-        let creature_data =
-            fs::read_to_string(train_dir.creatures.join(format!("id-{}.ron", id))).expect("Unable to read existing creature data file");
-        let creature_de: CreatureMorphologyGraph = ron::de::from_str(&creature_data).expect("Unable to parse creature data");
+/// The environment files of sessions from before the environment held the
+/// physics settings, which named either the ground or the fluid
+#[derive(Deserialize)]
+enum LegacyEnvironment {
+    Ground(Terrain),
+    Water(FluidConfig),
+}
 
-        population.push(creature_de);
+/// Loads the environment a session was trained in, if it was saved and can be
+/// read
+pub fn load_environment(session: &str) -> Option<EnvironmentConfig> {
+    let path = train_path(session).session.join("environment.ron");
+    if !path.exists() {
+        return None;
     }
+    let data = fs::read_to_string(path).expect("Unable to read existing environment file");
+    if let Ok(environment) = ron::de::from_str(&data) {
+        return Some(environment);
+    }
+    match ron::de::from_str(&data) {
+        Ok(LegacyEnvironment::Ground(terrain)) => Some(EnvironmentConfig::default().with_terrain(terrain)),
+        Ok(LegacyEnvironment::Water(fluid)) => Some(EnvironmentConfig { fluid: Some(fluid), ..EnvironmentConfig::water() }),
+        Err(_) => {
+            println!("WARNING: Unable to parse environment file, using the default environment");
+            None
+        },
+    }
+}
+
+/// Loads the creatures on the Pareto front of the last generation, along with
+/// its number
+pub fn load_pareto_front(session: &str) -> Result<(usize, Vec<CreatureMorphologyGraph>), String> {
+    let missing = || String::from("Session was not trained with NSGA-II");
+    let dir = train_path(session).session.join("pareto-fronts");
+    let generation = fs::read_dir(&dir)
+        .map_err(|_| missing())?
+        .filter_map(|file| {
+            let name = file.ok()?.file_name().into_string().ok()?;
+            name.strip_prefix("gen-")?.strip_suffix(".dat")?.parse::<usize>().ok()
+        })
+        .max()
+        .ok_or_else(missing)?;
+
+    let file = format!("gen-{}.dat", generation);
+    let data = fs::read_to_string(dir.join(&file)).map_err(|_| missing())?;
+    let records: Vec<CreatureRecord> = read_records(&data, &file);
+    if records.is_empty() {
+        return Err(String::from("The Pareto front is empty"));
+    }
+    Ok((generation, records.iter().map(|record| load_creature(session, record.id)).collect()))
+}
+
+/// Loads the creatures of one island from the last generation
+pub fn load_island(session: &str, island: usize) -> Vec<CreatureMorphologyGraph> {
+    let train_dir = train_path(session);
+    read_generation_records(&train_dir)
+        .iter()
+        .filter(|record| record.island == Some(island))
+        .map(|record| load_creature(session, record.id))
+        .collect()
+}
 
-    population
+/// The MAP-Elites grid of the session, if it was trained with one
+pub fn load_map_elites(session: &str) -> Option<MapElites> {
+    let path = train_path(session).session.join("map-elites.ron");
+    if !path.exists() {
+        return None;
+    }
+    let data = fs::read_to_string(path).expect("Unable to read existing MAP-Elites file");
+    Some(ron::de::from_str(&data).expect("Unable to parse MAP-Elites file"))
+}
+
+
+/// Saves the champions a competition session plays against
+pub fn write_champions(session: &str, champions: &[CreatureMorphologyGraph]) {
+    let path = train_path(session).session.join("champions.ron");
+    let serialized = ron::ser::to_string(champions).unwrap();
+    fs::write(path, serialized).expect("Failed to write champions file");
+}
+
+/// The champions of a competition session, if it saved any
+pub fn load_champions(session: &str) -> Option<Vec<CreatureMorphologyGraph>> {
+    let path = train_path(session).session.join("champions.ron");
+    if !path.exists() {
+        return None;
+    }
+    let data = fs::read_to_string(path).expect("Unable to read existing champions file");
+    Some(ron::de::from_str(&data).expect("Unable to parse champions file"))
+}
+
+
+/// Saves the trajectories recorded while testing a generation, if there are any
+fn write_trajectories(session: &str, generation: usize, trajectories: &[Trajectory]) {
+    if trajectories.is_empty() {
+        return;
+    }
+    let dir = train_path(session).session.join("trajectories");
+    fs::create_dir_all(&dir).expect("Unable to create trajectories directory");
+    fs::write(dir.join(format!("gen-{}.csv", generation)), Trajectory::to_csv(trajectories)).expect("Failed to write trajectories file");
+}
+
+/// The trajectories recorded while testing the given generation, or the latest
+/// recorded generation, along with its number
+pub fn load_trajectories(session: &str, generation: Option<usize>) -> Result<(usize, Vec<Trajectory>), String> {
+    let missing = || String::from("No trajectories recorded for this generation");
+    let dir = train_path(session).session.join("trajectories");
+    let generation = match generation {
+        Some(generation) => generation,
+        None => fs::read_dir(&dir)
+            .map_err(|_| missing())?
+            .filter_map(|file| {
+                let name = file.ok()?.file_name().into_string().ok()?;
+                name.strip_prefix("gen-")?.strip_suffix(".csv")?.parse::<usize>().ok()
+            })
+            .max()
+            .ok_or_else(missing)?,
+    };
+    let file = dir.join(format!("gen-{}.csv", generation));
+    let data = fs::read_to_string(&file).map_err(|_| missing())?;
+    let trajectories = Trajectory::from_csv(&data).ok_or_else(|| format!("Unable to parse trajectories file {}", file.display()))?;
+    Ok((generation, trajectories))
+}
+
+
+/// Saves the hall of fame, replacing the creature files of those that left it
+fn write_hall_of_fame(train_dir: &TrainingPaths, hall_of_fame: &HallOfFame) {
+    fs::create_dir_all(&train_dir.hall_of_fame).expect("Unable to create hall of fame directory");
+    let file_name = |entry: &HallOfFameEntry| format!("id-{}.ron", entry.creature.creature.0);
+    for file in fs::read_dir(&train_dir.hall_of_fame).expect("Unable to read hall of fame directory") {
+        let path = file.expect("Unable to read hall of fame directory").path();
+        if !hall_of_fame.entries.iter().any(|entry| path.ends_with(file_name(entry))) {
+            fs::remove_file(path).expect("Unable to remove old hall of fame creature");
+        }
+    }
+
+    let mut data = String::from("--- Hall of fame ---\n\n");
+    for entry in hall_of_fame.entries.iter() {
+        let creature_file = train_dir.hall_of_fame.join(file_name(entry));
+        if !creature_file.exists() {
+            let serialized = ron::ser::to_string_pretty(&entry.creature, ron::ser::PrettyConfig::default()).unwrap();
+            fs::write(creature_file, serialized).expect("Failed to write hall of fame creature file");
+        }
+        let record = HallOfFameRecord { id: entry.creature.creature.0, fitness: entry.fitness, generation: entry.generation };
+        data.push_str(&format!("{}\n", ron::ser::to_string(&record).unwrap()));
+    }
+    fs::write(train_dir.session.join("hall-of-fame.dat"), data).expect("Failed to write hall of fame file");
+}
+
+/// The hall of fame of the session, fittest first
+pub fn load_hall_of_fame(session: &str) -> Vec<HallOfFameEntry> {
+    let train_dir = train_path(session);
+    let Ok(data) = fs::read_to_string(train_dir.session.join("hall-of-fame.dat")) else { return Vec::new() };
+
+    read_records::<HallOfFameRecord>(&data, "hall-of-fame.dat")
+        .into_iter()
+        .map(|record| {
+            let creature_data = fs::read_to_string(train_dir.hall_of_fame.join(format!("id-{}.ron", record.id)))
+                .expect("Unable to read existing hall of fame creature file");
+            let creature = ron::de::from_str(&creature_data).expect("Unable to parse hall of fame creature");
+            HallOfFameEntry { creature, fitness: record.fitness, generation: record.generation }
+        })
+        .collect()
 }
 
+
+/// The fittest creature of the session, from the hall of fame if it has one
+/// or else the best creature of the last generation
 pub fn grab_best_creature(session: &str) -> Option<usize> {
+    if let Some(best) = load_hall_of_fame(session).first() {
+        return Some(best.creature.creature.0);
+    }
     let train_dir = train_path(session);
     let session_data = train_dir.session.join("session.dat");
     if session_data.exists() {
@@ -128,6 +374,50 @@ pub fn grab_best_creature(session: &str) -> Option<usize> {
     None
 }
 
+/// The directory holding the archives of an island's populator
+pub fn island_path(session: &str, island: usize) -> PathBuf {
+    train_path(session).session.join(format!("islands/island-{}", island))
+}
+
+
+/// Saves the archives kept by the populator's selection methods, if any
+fn write_populator_state(dir: &Path, populator: &GenerationPopulator) {
+    if let Some(novelty) = &populator.novelty {
+        let archive_ser = ron::ser::to_string(&novelty.archive).unwrap();
+        fs::write(dir.join("novelty-archive.ron"), archive_ser).expect("Failed to write novelty archive file");
+    }
+    if let Some(map_elites) = &populator.map_elites {
+        let map_elites_ser = ron::ser::to_string(map_elites).unwrap();
+        fs::write(dir.join("map-elites.ron"), map_elites_ser).expect("Failed to write MAP-Elites file");
+    }
+    if let Some(speciation) = &populator.speciation {
+        let speciation_ser = ron::ser::to_string(speciation).unwrap();
+        fs::write(dir.join("species.ron"), speciation_ser).expect("Failed to write species file");
+    }
+}
+
+
+/// Restores the archives saved by `write_populator_state` for the selection
+/// methods the populator uses
+fn load_populator_state(dir: &Path, populator: &mut GenerationPopulator) {
+    if let Some(novelty) = populator.novelty.as_mut() {
+        if let Ok(archive_data) = fs::read_to_string(dir.join("novelty-archive.ron")) {
+            novelty.archive = ron::de::from_str(&archive_data).expect("Failed to parse novelty archive file");
+        }
+    }
+    if let Some(map_elites) = populator.map_elites.as_mut() {
+        if let Ok(map_elites_data) = fs::read_to_string(dir.join("map-elites.ron")) {
+            *map_elites = ron::de::from_str(&map_elites_data).expect("Failed to parse MAP-Elites file");
+        }
+    }
+    if let Some(speciation) = populator.speciation.as_mut() {
+        if let Ok(speciation_data) = fs::read_to_string(dir.join("species.ron")) {
+            *speciation = ron::de::from_str(&speciation_data).expect("Failed to parse species file");
+        }
+    }
+}
+
+
 fn remove_dir_contents<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<()> {
     for entry in fs::read_dir(path)? {
         fs::remove_file(entry?.path())?;
@@ -136,14 +426,74 @@ fn remove_dir_contents<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<()
 }
 
 
+/// The lines of session.dat for the settings a resumed session keeps when they
+/// aren't given again
+fn session_settings<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
+    build_conf: &CreatureBuilderConfig,
+    generation: &EvolutionGeneration<F>,
+) -> String {
+    let mut data = String::new();
+    if let Some(budget) = build_conf.behavior.energy_budget {
+        data.push_str(&format!("\nenergy_budget = [{}]", budget));
+    }
+    if let Some(actuation) = build_conf.behavior.actuation {
+        data.push_str(&format!("\nactuation = [{}]", ron::ser::to_string(&actuation).unwrap()));
+    }
+    if !generation.fitness_weights.is_empty() {
+        let weights: Vec<String> = generation.fitness_weights.iter().map(|weight| weight.to_string()).collect();
+        data.push_str(&format!("\nfitness_weights = [{}]", weights.join(", ")));
+    }
+    if let Some(heading) = generation.fitness_heading {
+        data.push_str(&format!("\nfitness_heading = [{}, {}]", heading.x, heading.y));
+    }
+    data
+}
+
+/// Restores the behavior settings a session was trained with that weren't set
+/// otherwise
+fn apply_behavior_settings(data: &str, build_conf: &mut CreatureBuilderConfig) {
+    if build_conf.behavior.energy_budget.is_none() {
+        build_conf.behavior.energy_budget =
+            session_setting(data, "energy_budget").map(|budget| budget.parse().expect("Invalid session.dat file"));
+    }
+    if build_conf.behavior.actuation.is_none() {
+        build_conf.behavior.actuation =
+            session_setting(data, "actuation").map(|actuation| ron::de::from_str(actuation).expect("Invalid session.dat file"));
+    }
+}
+
+/// Restores the behavior settings the session was trained with, so creatures
+/// are played back the way they were tested
+pub fn load_behavior_settings(session: &str, build_conf: &mut CreatureBuilderConfig) {
+    if let Ok(data) = fs::read_to_string(train_path(session).session.join("session.dat")) {
+        apply_behavior_settings(&data, build_conf);
+    }
+}
+
+/// The value of an optional `key = [value]` line of session.dat
+fn session_setting<'a>(data: &'a str, key: &str) -> Option<&'a str> {
+    let start = data.find(&format!("\n{} = [", key))? + key.len() + 5;
+    let length = data[start..].find(']')?;
+    Some(&data[start..start + length])
+}
+
+
+#[allow(clippy::too_many_arguments)]
 pub fn write_generation<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
     generation: Res<EvolutionGeneration<F>>,
-    populator: Res<GenerationPopulator>,
+    mut populator: ResMut<GenerationPopulator>,
+    islands: Option<Res<IslandModel>>,
     gen_test_conf: Res<GenerationTestingConfig>,
+    curriculum: Res<Curriculum>,
+    trajectories: Option<ResMut<TrajectoryRecorder>>,
+    build_conf: Res<CreatureBuilderConfig>,
     mut next_state: ResMut<NextState<EvolutionState>>,
 ) {
     let train_dir = train_path(&gen_test_conf.session);
     let cur_gen = generation.current_generation;
+    if let Some(mut trajectories) = trajectories {
+        write_trajectories(&gen_test_conf.session, cur_gen, &trajectories.take());
+    }
 
     remove_dir_contents(&train_dir.creatures).expect("Unable to remove old creatures");
     for creature in generation.population.iter() {
@@ -154,34 +504,74 @@ pub fn write_generation<F: EvolutionFitnessEval + Send + Sync + Default + 'stati
         }
     }
 
-    let gen_file = train_dir.session.join("last-gen.dat");
-    let mut gen = String::new();
-
-    gen.push_str(&format!("--- Generation {} ---\n\n", cur_gen));
+    let records: Vec<CreatureRecord> = generation
+        .population
+        .iter()
+        .zip(generation.fitnesses.iter())
+        .zip(generation.populate_flags.iter())
+        .enumerate()
+        .map(|(i, ((creature, fitness), flags))| CreatureRecord {
+            id: creature.creature.0,
+            fitness: *fitness,
+            flags: *flags,
+            objectives: generation.objectives.get(i).cloned(),
+            behavior: generation.behaviors.get(i).cloned(),
+            traits: generation.traits.get(i).copied(),
+            species: generation.species.get(i).copied(),
+            island: generation.islands.get(i).copied(),
+            trials: generation.trial_fitnesses.get(i).cloned(),
+            diagnostics: generation.diagnostics.get(i).copied(),
+        })
+        .collect();
+
+    let mut gen = format!("--- Generation {} ---\n\n", cur_gen);
+    for record in records.iter() {
+        gen.push_str(&format!("{}\n", ron::ser::to_string(record).unwrap()));
+    }
+    fs::write(train_dir.session.join("last-gen.dat"), gen).expect("Failed to write generation file");
+
+    if populator.selection == SelectionMethod::Nsga2 && generation.objectives.len() == generation.population.len() {
+        let front_dir = train_dir.session.join("pareto-fronts");
+        fs::create_dir_all(&front_dir).expect("Unable to create pareto front directory");
+        let mut front = format!("--- Pareto front of generation {} ---\n\n", cur_gen);
+        for i in non_dominated_sort(&generation.objectives).into_iter().next().unwrap_or_default() {
+            front.push_str(&format!("{}\n", ron::ser::to_string(&records[i]).unwrap()));
+        }
+        fs::write(front_dir.join(format!("gen-{}.dat", cur_gen)), front).expect("Failed to write pareto front file");
+    }
 
-    for ((creature, fitness), flags) in generation.population.iter().zip(generation.fitnesses.iter()).zip(generation.populate_flags.iter())
-    {
-        let flag_ser = ron::ser::to_string(&flags).unwrap();
-        gen.push_str(&format!("id: [{}]  fitness: [{}]  flags: [{}]\n", creature.creature.0, fitness, flag_ser));
+    for (creature, fitness) in generation.population.iter().zip(generation.fitnesses.iter()) {
+        populator.hall_of_fame.insert(creature, *fitness, cur_gen);
+    }
+    write_hall_of_fame(&train_dir, &populator.hall_of_fame);
+
+    write_populator_state(&train_dir.session, &populator);
+    if let Some(islands) = &islands {
+        for (i, island) in islands.islands.iter().enumerate() {
+            let island_dir = island_path(&gen_test_conf.session, i);
+            fs::create_dir_all(&island_dir).expect("Unable to create island directory");
+            write_populator_state(&island_dir, island);
+        }
     }
-    fs::write(gen_file, gen).expect("Failed to write generation file");
 
     let session_data = train_dir.session.join("session.dat");
     if session_data.exists() {
         let mut data = String::new();
         data.push_str(&format!(
             "--- Session data file ---\n\nname = [{}]\ncurrent_generation = [{}]\ncurrent_id = [{}]\nbest_fitness = [{}]\nbest_creature = \
-             [{}]",
-            &gen_test_conf.session, cur_gen, populator.current_id, populator.best_fitness, populator.best_creature,
+             [{}]\ncurriculum_stage = [{}]",
+            &gen_test_conf.session, cur_gen, populator.current_id, populator.best_fitness, populator.best_creature, curriculum.current,
         ));
+        data.push_str(&session_settings(&build_conf, &generation));
         fs::write(session_data, data).expect("Failed to write session data file");
     } else {
         let mut data = String::new();
         data.push_str(&format!(
             "--- Session data file ---\n\nname = [{}]\ncurrent_generation = [-1]\ncurrent_id = [-1]\nbest_fitness = \
-             [-1000000000000.0]\nbest_creature = [0]",
-            &gen_test_conf.session
+             [{}]\nbest_creature = [0]\ncurriculum_stage = [0]",
+            &gen_test_conf.session, PENALTY_FITNESS
         ));
+        data.push_str(&session_settings(&build_conf, &generation));
         fs::write(session_data, data).expect("Failed to write session data file");
     }
 
@@ -192,13 +582,23 @@ pub fn write_generation<F: EvolutionFitnessEval + Send + Sync + Default + 'stati
 pub fn load_session<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
     generation: &mut EvolutionGeneration<F>,
     populator: &mut GenerationPopulator,
-    gen_test_conf: &GenerationTestingConfig,
+    islands: Option<&mut IslandModel>,
+    gen_test_conf: &mut GenerationTestingConfig,
+    curriculum: &mut Curriculum,
+    build_conf: &mut CreatureBuilderConfig,
 ) {
     let train_dir = train_path(&gen_test_conf.session);
     let session_data = train_dir.session.join("session.dat");
 
     generation.population.clear();
     generation.fitnesses.clear();
+    generation.objectives.clear();
+    generation.behaviors.clear();
+    generation.traits.clear();
+    generation.species.clear();
+    generation.islands.clear();
+    generation.trial_fitnesses.clear();
+    generation.diagnostics.clear();
     generation.populate_flags.clear();
 
     if session_data.exists() {
@@ -217,6 +617,25 @@ pub fn load_session<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
         let cur_id_data: isize = grab_session_data(&data, "current_id", 14);
         let best_fitness: f32 = grab_session_data(&data, "best_fitness", 16);
         let best_creature: usize = grab_session_data(&data, "best_creature", 17);
+        // Sessions from before curricula were added are always in the first stage
+        let curriculum_stage: usize = if data.contains("curriculum_stage") { grab_session_data(&data, "curriculum_stage", 20) } else { 0 };
+
+        apply_behavior_settings(&data, build_conf);
+        let floats = |setting: &str| -> Vec<f32> {
+            setting.split(',').map(|value| value.trim().parse().expect("Invalid session.dat file")).collect()
+        };
+        if generation.fitness_weights.is_empty() {
+            generation.fitness_weights = session_setting(&data, "fitness_weights").map_or(Vec::new(), floats);
+        }
+        if generation.fitness_heading.is_none() {
+            if let Some(&[x, z]) = session_setting(&data, "fitness_heading").map(floats).as_deref() {
+                let heading = Vec2::new(x, z);
+                generation.fitness_heading = Some(heading);
+                if let Some(push) = gen_test_conf.push.as_mut() {
+                    *push = push.clone().with_heading(heading);
+                }
+            }
+        }
 
         if cur_gen_data < 0 {
             return;
@@ -225,27 +644,42 @@ pub fn load_session<F: EvolutionFitnessEval + Send + Sync + Default + 'static>(
         populator.current_id = cur_id_data as usize;
         populator.best_fitness = best_fitness;
         populator.best_creature = best_creature;
+        curriculum.current = curriculum_stage.min(curriculum.stages.len().saturating_sub(1));
+
+        load_populator_state(&train_dir.session, populator);
+        populator.hall_of_fame.entries = load_hall_of_fame(&gen_test_conf.session);
+        populator.hall_of_fame.entries.truncate(populator.hall_of_fame.size);
+        if let Some(islands) = islands {
+            for (i, island) in islands.islands.iter_mut().enumerate() {
+                load_populator_state(&island_path(&gen_test_conf.session, i), island);
+            }
+        }
 
-        let gen_data = fs::read_to_string(train_dir.session.join("last-gen.dat")).expect("Unable to read existing generation file");
-
-        for line in gen_data[..gen_data.len() - 1].split('\n').skip(2) {
-            let mut elements = line.split("  ");
-            let mut grab_value = |offset: usize| {
-                let text = &elements.next().expect("Invalid generation file")[offset..];
-                &text[..text.len() - 1]
-            };
-
-            let id: usize = grab_value(5).parse().expect("Failed to parse id in generation file");
-            let fitness: f32 = grab_value(10).parse().expect("Failed to parse fitness in generation file");
-            let flags: CreaturePopulateFlag = ron::de::from_str(grab_value(8)).expect("Failed to parse flag in generation file");
-
-            let creature_data =
-                fs::read_to_string(train_dir.creatures.join(format!("id-{}.ron", id))).expect("Unable to read existing creature data file");
-            let creature_de: CreatureMorphologyGraph = ron::de::from_str(&creature_data).expect("Unable to parse creature data");
-
-            generation.population.push(creature_de);
-            generation.fitnesses.push(fitness);
-            generation.populate_flags.push(flags);
+        for record in read_generation_records(&train_dir) {
+            generation.population.push(load_creature(&gen_test_conf.session, record.id));
+            generation.fitnesses.push(record.fitness);
+            generation.populate_flags.push(record.flags);
+            if let Some(objectives) = record.objectives {
+                generation.objectives.push(objectives);
+            }
+            if let Some(behavior) = record.behavior {
+                generation.behaviors.push(behavior);
+            }
+            if let Some(traits) = record.traits {
+                generation.traits.push(traits);
+            }
+            if let Some(species) = record.species {
+                generation.species.push(species);
+            }
+            if let Some(island) = record.island {
+                generation.islands.push(island);
+            }
+            if let Some(trials) = record.trials {
+                generation.trial_fitnesses.push(trials);
+            }
+            if let Some(diagnostics) = record.diagnostics {
+                generation.diagnostics.push(diagnostics);
+            }
         }
     }
 }
@@ -9,6 +9,7 @@ use creature_builder::{
         value::ExprValue,
         Expr,
     },
+    joint::CreatureJointId,
 };
 use rand::{rngs::ThreadRng, Rng};
 
@@ -22,7 +23,11 @@ pub struct RandomExprParams {
     pub const_range: Range<f32>,
     pub max_depth: usize,
     pub min_depth: usize,
-    joint_count: usize,
+    joints: Vec<CreatureJointId>,
+    central_count: usize,
+    /// Whether values can refer to the joint the expression drives, which
+    /// central nodes don't have
+    local_joint: bool,
 }
 
 impl RandomExprParams {
@@ -30,8 +35,18 @@ impl RandomExprParams {
         Expr { root: self.build(rng, 0) }
     }
 
-    pub fn with_joint_count(mut self, count: usize) -> Self {
-        self.joint_count = count;
+    pub fn with_joints(mut self, joints: Vec<CreatureJointId>) -> Self {
+        self.joints = joints;
+        self
+    }
+
+    pub fn with_central_count(mut self, count: usize) -> Self {
+        self.central_count = count;
+        self
+    }
+
+    pub fn with_local_joint(mut self, local_joint: bool) -> Self {
+        self.local_joint = local_joint;
         self
     }
 
@@ -54,6 +69,24 @@ impl RandomExprParams {
         }
     }
 
+    /// Chooses a random value, skipping kinds that have nothing to refer to
+    pub fn random_value(&self, rng: &mut ThreadRng) -> CreatureContextElement {
+        match rng.gen_range(0usize..4) {
+            1 if !self.joints.is_empty() => CreatureContextElement::GlobalJoint {
+                element: self.random_element(rng),
+                joint: self.joints[rng.gen_range(0..self.joints.len())],
+            },
+            2 if self.central_count > 0 => CreatureContextElement::Central { index: rng.gen_range(0..self.central_count) },
+            3 => CreatureContextElement::Time,
+            _ if self.local_joint => CreatureContextElement::LocalJoint { element: self.random_element(rng) },
+            _ if !self.joints.is_empty() => CreatureContextElement::GlobalJoint {
+                element: self.random_element(rng),
+                joint: self.joints[rng.gen_range(0..self.joints.len())],
+            },
+            _ => CreatureContextElement::Time,
+        }
+    }
+
     pub fn build_single(&self, rng: &mut ThreadRng) -> Box<ExprNode> {
         Box::new(self.build(rng, 0))
     }
@@ -83,12 +116,7 @@ impl RandomExprParams {
                 _ => unreachable!(),
             }
         } else if r < value_weight {
-            ExprNode::Value(match rng.gen_range(0usize..3) {
-                0 => CreatureContextElement::LocalJoint { element: self.random_element(rng) },
-                1 => CreatureContextElement::GlobalJoint { element: self.random_element(rng), joint: rng.gen_range(0..self.joint_count) },
-                2 => CreatureContextElement::Time,
-                _ => unreachable!(),
-            })
+            ExprNode::Value(self.random_value(rng))
         } else {
             ExprNode::Constant(ExprValue(rng.gen_range(self.const_range.clone())))
         }
@@ -97,7 +125,16 @@ impl RandomExprParams {
 
 impl Default for RandomExprParams {
     fn default() -> Self {
-        Self { value_weight: 20, const_weight: 20, const_range: -10.0..10.0, min_depth: 1, max_depth: 3, joint_count: 1 }
+        Self {
+            value_weight: 20,
+            const_weight: 20,
+            const_range: -10.0..10.0,
+            min_depth: 1,
+            max_depth: 3,
+            joints: Vec::new(),
+            central_count: 0,
+            local_joint: true,
+        }
     }
 }
 
@@ -153,13 +190,21 @@ impl<'a> MutateExpr<'a> {
         Self { expr, rng, params }
     }
 
-    pub fn with_joint_count(self, count: usize) -> Self {
-        self.params.new_expr.joint_count = count;
+    pub fn with_joints(self, joints: Vec<CreatureJointId>) -> Self {
+        self.params.new_expr.joints = joints;
         self
     }
 
-    pub fn set_joint_count(&mut self, count: usize) {
-        self.params.new_expr.joint_count = count;
+    pub fn set_joints(&mut self, joints: Vec<CreatureJointId>) {
+        self.params.new_expr.joints = joints;
+    }
+
+    pub fn set_central_count(&mut self, count: usize) {
+        self.params.new_expr.central_count = count;
+    }
+
+    pub fn set_local_joint(&mut self, local_joint: bool) {
+        self.params.new_expr.local_joint = local_joint;
     }
 
     pub fn inner(&'a self) -> &'a Expr {
@@ -206,26 +251,12 @@ impl<'a> MutateExpr<'a> {
         }
     }
 
-    fn random_element(&mut self) -> JointContextElement {
-        match self.rng.gen_range(0..3) {
-            0 => JointContextElement::ParentContact { face: LimbAttachFace::rand_field(self.rng) },
-            1 => JointContextElement::ChildContact { face: LimbAttachFace::rand_field(self.rng) },
-            _ => JointContextElement::JointAxis {
-                axis: match self.rng.gen_range(0..6) {
-                    0 => JointAxis::X,
-                    1 => JointAxis::Y,
-                    2 => JointAxis::Z,
-                    3 => JointAxis::AngX,
-                    4 => JointAxis::AngY,
-                    5 => JointAxis::AngZ,
-                    _ => unreachable!(),
-                },
-            },
+    fn random_joint(&mut self) -> Option<CreatureJointId> {
+        let joints = &self.params.new_expr.joints;
+        if joints.is_empty() {
+            return None;
         }
-    }
-
-    fn random_joint_index(&mut self) -> usize {
-        self.rng.gen_range(0usize..self.params.new_expr.joint_count)
+        Some(joints[self.rng.gen_range(0..joints.len())])
     }
 
     fn mutate_node(&mut self, node: &ExprNode) -> Box<ExprNode> {
@@ -234,27 +265,23 @@ impl<'a> MutateExpr<'a> {
         if let ExprNode::Value(value) = node {
             let val = if self.rng.gen_bool(self.params.value_change_freq as f64) {
                 if self.rng.gen_bool(self.params.value_change_type_freq as f64) {
-                    Box::new(ExprNode::Value(match value {
-                        CreatureContextElement::LocalJoint { element } => match self.rng.gen_range(0..2) {
-                            0 => CreatureContextElement::GlobalJoint { element: *element, joint: self.random_joint_index() },
-                            _ => CreatureContextElement::Time,
-                        },
-                        CreatureContextElement::GlobalJoint { element, .. } => match self.rng.gen_range(0..2) {
-                            0 => CreatureContextElement::LocalJoint { element: *element },
-                            _ => CreatureContextElement::Time,
-                        },
-                        CreatureContextElement::Time => match self.rng.gen_range(0..2) {
-                            0 => CreatureContextElement::LocalJoint { element: self.random_element() },
-                            _ => CreatureContextElement::GlobalJoint { element: self.random_element(), joint: self.random_joint_index() },
-                        },
-                    }))
+                    Box::new(ExprNode::Value(self.params.new_expr.random_value(self.rng)))
                 } else {
                     Box::new(ExprNode::Value(match value {
-                        CreatureContextElement::LocalJoint { element } => {
+                        CreatureContextElement::LocalJoint { element } if self.params.new_expr.local_joint => {
                             CreatureContextElement::LocalJoint { element: self.mutate_element(element) }
                         },
-                        CreatureContextElement::GlobalJoint { element, .. } => {
-                            CreatureContextElement::GlobalJoint { element: self.mutate_element(element), joint: self.random_joint_index() }
+                        CreatureContextElement::LocalJoint { .. } => self.params.new_expr.random_value(self.rng),
+                        CreatureContextElement::GlobalJoint { element, joint } => CreatureContextElement::GlobalJoint {
+                            element: self.mutate_element(element),
+                            joint: self.random_joint().unwrap_or(*joint),
+                        },
+                        CreatureContextElement::Central { index } => CreatureContextElement::Central {
+                            index: if self.params.new_expr.central_count > 0 {
+                                self.rng.gen_range(0..self.params.new_expr.central_count)
+                            } else {
+                                *index
+                            },
                         },
                         CreatureContextElement::Time => CreatureContextElement::Time,
                     }))
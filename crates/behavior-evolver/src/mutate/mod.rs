@@ -2,19 +2,22 @@ use std::{collections::HashSet, ops::Range};
 
 use bevy::{math::Vec3, transform::components::Transform};
 use bevy_rapier3d::dynamics::JointAxesMask;
-use creature_builder::{builder::node::CreatureMorphologyGraph, effector::CreatureJointEffector, CreatureId};
+use creature_builder::{builder::node::CreatureMorphologyGraph, effector::CreatureJointEffector, joint::CreatureJointId, CreatureId};
 use data_structure_utils::graphs::directed::{DirectedGraph, NodeID};
 use rand::{rngs::ThreadRng, Rng};
 use rand_distr::Normal;
+use serde::{Deserialize, Serialize};
 
 use self::{
     edge::{MutateEdge, MutateEdgeParams, RandomEdgeParams},
     expr::{MutateExpr, MutateExprParams, RandomExprParams},
+    neural::{MutateNeural, MutateNeuralParams, RandomNeuralParams},
     node::{MutateNode, MutateNodeParams, RandomNodeParams},
 };
 
 pub mod edge;
 pub mod expr;
+pub mod neural;
 pub mod node;
 
 
@@ -66,13 +69,28 @@ impl MutateFieldParams {
 }
 
 
+/// The representation used to drive the joints of new random creatures
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum CreatureControllerType {
+    /// An independent expression tree for every unlocked joint axis
+    #[default]
+    Expr,
+    /// A single recurrent neural network shared by every joint
+    Neural,
+}
+
+
 pub struct RandomMorphologyParams {
     pub rand_node: RandomNodeParams,
     pub rand_edge: RandomEdgeParams,
     pub rand_expr: RandomExprParams,
+    pub rand_neural: RandomNeuralParams,
+    pub controller: CreatureControllerType,
     pub rand_root: Range<f32>,
     pub nodes: Range<usize>,
     pub edges: Range<usize>,
+    /// The number of central nodes
+    pub central: Range<usize>,
 }
 
 impl RandomMorphologyParams {
@@ -109,7 +127,7 @@ impl RandomMorphologyParams {
         let n_nodes = graph.nodes.len();
         let node_ids: Vec<_> = graph.nodes.keys().copied().collect();
 
-        let mut morph = CreatureMorphologyGraph { graph, creature, root: root_transform };
+        let mut morph = CreatureMorphologyGraph { graph, creature, root: root_transform, brain: None, central: Vec::new() };
 
         let graph_root = node_ids[rng.gen_range(0..n_nodes)];
         morph.set_root(graph_root);
@@ -118,8 +136,20 @@ impl RandomMorphologyParams {
             morph.graph.add_edge(self.rand_edge.build_edge(rng), graph_root, node_ids[rng.gen_range(0..n_nodes)]);
         }
 
-        let n_joints = morph.edges_len();
-        let rand_expr = self.rand_expr.clone().with_joint_count(n_joints);
+        if self.controller == CreatureControllerType::Neural {
+            morph.brain = Some(self.rand_neural.build_brain(rng));
+            return morph;
+        }
+
+        let joints = morph.joint_ids();
+        for i in 0..rng.gen_range(self.central.clone()) {
+            // Central nodes can only read the central nodes before them, and
+            // aren't evaluated for any one joint
+            let rand_expr = self.rand_expr.clone().with_joints(joints.clone()).with_central_count(i).with_local_joint(false);
+            morph.central.push(rand_expr.build_expr(rng));
+        }
+
+        let rand_expr = self.rand_expr.clone().with_joints(joints).with_central_count(morph.central.len());
         for edge in morph.edges_mut() {
             for (i, expr) in edge.data.effectors.effectors.iter_mut().enumerate() {
                 if !edge.data.locked_axes.contains(JointAxesMask::from_bits(1 << i).unwrap()) {
@@ -138,9 +168,12 @@ impl Default for RandomMorphologyParams {
             rand_node: RandomNodeParams::default(),
             rand_edge: RandomEdgeParams::default(),
             rand_expr: RandomExprParams::default(),
+            rand_neural: RandomNeuralParams::default(),
+            controller: CreatureControllerType::default(),
             rand_root: 0.5..1.5,
             nodes: 3..6,
             edges: 2..4,
+            central: 0..3,
         }
     }
 }
@@ -151,6 +184,7 @@ pub struct MutateMorphologyParams {
     pub node: MutateNodeParams,
     pub edge: MutateEdgeParams,
     pub expr: MutateExprParams,
+    pub neural: MutateNeuralParams,
     pub rand_node: RandomNodeParams,
     pub rand_edge: RandomEdgeParams,
     /// The frequency at which edges choose a new node to point to
@@ -160,6 +194,12 @@ pub struct MutateMorphologyParams {
     /// The frequency at which edges are added
     pub edge_add_freq: f32,
     pub expr_mut_freq: f32,
+    /// The frequency at which the neural controller, if any, is mutated
+    pub neural_mut_freq: f32,
+    /// The frequency at which central nodes are added
+    pub central_add_freq: f32,
+    /// The frequency at which central nodes are deleted
+    pub central_del_freq: f32,
     pub root_size: MutateFieldParams,
     /// The inverse scale at which the sizes of creatures reduce the frequency
     /// of mutations
@@ -195,12 +235,16 @@ impl Default for MutateMorphologyParams {
                 limit_axes: MutateFieldParams::new(0.2, 0.0, 0.03).unwrap().in_range(0.0..PI),
             },
             expr: MutateExprParams::default(),
+            neural: MutateNeuralParams::default(),
             rand_node: RandomNodeParams::default(),
             rand_edge: RandomEdgeParams::default(),
             edge_change_freq: 0.1,
             edge_del_freq: 0.1,
             edge_add_freq: 0.1,
             expr_mut_freq: 0.15,
+            neural_mut_freq: 0.15,
+            central_add_freq: 0.02,
+            central_del_freq: 0.02,
             root_size: MutateFieldParams::new(0.05, 0.0, 0.05).unwrap().in_range(0.25..2.0),
             size_inv_scale: 1.0,
         }
@@ -304,21 +348,70 @@ impl<'a> MutateMorphology<'a> {
         };
 
         // Step 6: mutate nested expr graphs
-        let n_joints = self.morph.edges_len();
         let mut_freq = self.params.expr_mut_freq / self.morph.edges_len() as f32;
-        for edge in self.morph.edges_mut() {
-            let freq_adjusted = mut_freq / edge.data.effectors.effectors.iter().filter(|x| x.is_some()).count() as f32;
-            for expr_opt in edge.data.effectors.effectors.iter_mut() {
-                let Some(expr) = expr_opt else { continue };
-                if self.rng.gen_bool(freq_adjusted as f64) {
-                    let mut mutate = MutateExpr::new(&mut expr.expr, self.rng, &mut self.params.expr);
-                    mutate.set_joint_count(n_joints);
-                    mutate.mutate();
+        let mut mutated_exprs = Vec::new();
+        for edge in self.morph.edge_ids() {
+            let effectors = &self.morph.graph.get_edge(edge).unwrap().data.effectors.effectors;
+            let freq_adjusted = mut_freq / effectors.iter().filter(|x| x.is_some()).count() as f32;
+            for (i, expr_opt) in effectors.iter().enumerate() {
+                if expr_opt.is_some() && self.rng.gen_bool(freq_adjusted as f64) {
+                    mutated_exprs.push((edge, i));
                 }
             }
         }
+        let central_freq = self.params.expr_mut_freq / self.morph.central.len().max(1) as f32;
+        let mutated_central: Vec<_> = (0..self.morph.central.len()).filter(|_| self.rng.gen_bool(central_freq as f64)).collect();
+
+        // Evaluating the joint ids requires building the creature, so only do it when needed
+        let mut joints: Option<Vec<CreatureJointId>> = None;
+        let n_central = self.morph.central.len();
+        for (edge, i) in mutated_exprs {
+            let joints = joints.get_or_insert_with(|| self.morph.joint_ids()).clone();
+            let Some(expr) = self.morph.graph.get_edge_mut(edge).unwrap().data.effectors.effectors[i].as_mut() else { continue };
+            let mut mutate = MutateExpr::new(&mut expr.expr, self.rng, &mut self.params.expr);
+            mutate.set_joints(joints);
+            mutate.set_central_count(n_central);
+            mutate.set_local_joint(true);
+            mutate.mutate();
+        }
+        for i in mutated_central {
+            let joints = joints.get_or_insert_with(|| self.morph.joint_ids()).clone();
+            let mut mutate = MutateExpr::new(&mut self.morph.central[i], self.rng, &mut self.params.expr);
+            mutate.set_joints(joints);
+            mutate.set_central_count(i);
+            mutate.set_local_joint(false);
+            mutate.mutate();
+        }
+
+        // Step 7: add and remove central nodes
+        if self.morph.brain.is_none() {
+            if !self.morph.central.is_empty() && self.rng.gen_bool(self.params.central_del_freq as f64) {
+                let index = self.rng.gen_range(0..self.morph.central.len());
+                self.morph.central.remove(index);
+            }
+            if self.rng.gen_bool(self.params.central_add_freq as f64) {
+                let joints = joints.get_or_insert_with(|| self.morph.joint_ids()).clone();
+                let rand_expr = self
+                    .params
+                    .expr
+                    .new_expr
+                    .clone()
+                    .with_joints(joints)
+                    .with_central_count(self.morph.central.len())
+                    .with_local_joint(false);
+                self.morph.central.push(rand_expr.build_expr(self.rng));
+            }
+        }
+
+        // Step 8: mutate the neural controller
+        if let Some(brain) = self.morph.brain.as_mut() {
+            if self.rng.gen_bool(self.params.neural_mut_freq as f64) {
+                let mut mutate = MutateNeural::new(brain, self.rng, &mut self.params.neural);
+                mutate.mutate();
+            }
+        }
 
-        // Step 7: mutate root cube size
+        // Step 9: mutate root cube size
         if self.params.root_size.change(self.rng) {
             let axis = match self.rng.gen_range(0..3) {
                 0 => {
@@ -0,0 +1,145 @@
+use std::ops::Range;
+
+use creature_builder::neural::NeuralBrain;
+use rand::{rngs::ThreadRng, Rng};
+
+use super::MutateFieldParams;
+
+
+#[derive(Clone)]
+pub struct RandomNeuralParams {
+    pub hidden: Range<usize>,
+    pub weight: Range<f32>,
+    pub bias: Range<f32>,
+    /// The probability that any given connection exists in the new network
+    pub connection_freq: f32,
+}
+
+impl RandomNeuralParams {
+    pub fn build_brain(&self, rng: &mut ThreadRng) -> NeuralBrain {
+        let mut brain = NeuralBrain::new(rng.gen_range(self.hidden.clone()));
+
+        for weights in brain.hidden_weights.iter_mut().chain(brain.output_weights.iter_mut()) {
+            for weight in weights.iter_mut() {
+                if rng.gen_bool(self.connection_freq as f64) {
+                    *weight = rng.gen_range(self.weight.clone());
+                }
+            }
+        }
+        for bias in brain.hidden_biases.iter_mut().chain(brain.output_biases.iter_mut()) {
+            *bias = rng.gen_range(self.bias.clone());
+        }
+
+        brain
+    }
+}
+
+impl Default for RandomNeuralParams {
+    fn default() -> Self {
+        Self { hidden: 2..8, weight: -2.0..2.0, bias: -1.0..1.0, connection_freq: 0.3 }
+    }
+}
+
+
+#[derive(Clone)]
+pub struct MutateNeuralParams {
+    pub weight: MutateFieldParams,
+    pub bias: MutateFieldParams,
+    /// The frequency at which hidden neurons are added
+    pub neuron_add_freq: f32,
+    /// The frequency at which hidden neurons are removed
+    pub neuron_del_freq: f32,
+    /// The frequency at which connections are cut by setting their weight to
+    /// zero
+    pub connection_del_freq: f32,
+    pub max_hidden: usize,
+}
+
+impl MutateNeuralParams {
+    pub fn set_scale(&mut self, inv_scale: f32) {
+        self.weight.set_scale(inv_scale);
+        self.bias.set_scale(inv_scale);
+        self.connection_del_freq *= inv_scale;
+    }
+}
+
+impl Default for MutateNeuralParams {
+    fn default() -> Self {
+        Self {
+            weight: MutateFieldParams::new(2.0, 0.0, 0.25).unwrap().in_range(-10.0..10.0),
+            bias: MutateFieldParams::new(1.0, 0.0, 0.1).unwrap().in_range(-10.0..10.0),
+            neuron_add_freq: 0.05,
+            neuron_del_freq: 0.05,
+            connection_del_freq: 0.5,
+            max_hidden: 16,
+        }
+    }
+}
+
+
+pub struct MutateNeural<'a> {
+    rng: &'a mut ThreadRng,
+    brain: &'a mut NeuralBrain,
+    params: &'a mut MutateNeuralParams,
+}
+
+impl<'a> MutateNeural<'a> {
+    pub fn new(brain: &'a mut NeuralBrain, rng: &'a mut ThreadRng, params: &'a mut MutateNeuralParams) -> Self {
+        Self { brain, rng, params }
+    }
+
+    pub fn inner(&'a self) -> &'a NeuralBrain {
+        self.brain
+    }
+
+    pub fn into_inner(self) -> &'a NeuralBrain {
+        self.brain
+    }
+
+    pub fn mutate(&mut self) {
+        // Scale so that, on average, a fixed number of weights and biases change
+        // regardless of the size of the network
+        let n_weights = self.brain.hidden_weights.iter().chain(self.brain.output_weights.iter()).map(|x| x.len()).sum::<usize>();
+        let n_biases = self.brain.hidden_len() + self.brain.output_biases.len();
+        let scale = (n_weights + n_biases) as f32;
+        self.params.set_scale(1.0 / scale);
+
+        for weights in self.brain.hidden_weights.iter_mut().chain(self.brain.output_weights.iter_mut()) {
+            for weight in weights.iter_mut() {
+                if self.params.weight.change(self.rng) {
+                    *weight = self.params.weight.mutate(self.rng, *weight);
+                }
+                if self.rng.gen_bool(self.params.connection_del_freq as f64) {
+                    *weight = 0.0;
+                }
+            }
+        }
+        for bias in self.brain.hidden_biases.iter_mut().chain(self.brain.output_biases.iter_mut()) {
+            if self.params.bias.change(self.rng) {
+                *bias = self.params.bias.mutate(self.rng, *bias);
+            }
+        }
+
+        self.params.set_scale(scale);
+
+        if self.brain.hidden_len() > 1 && self.rng.gen_bool(self.params.neuron_del_freq as f64) {
+            let index = self.rng.gen_range(0..self.brain.hidden_len());
+            self.brain.remove_hidden(index);
+        }
+        if self.brain.hidden_len() < self.params.max_hidden && self.rng.gen_bool(self.params.neuron_add_freq as f64) {
+            self.brain.add_hidden();
+            let index = self.brain.hidden_len() - 1;
+            // Splice the new neuron into the network with one input and one output
+            let input = self.rng.gen_range(0..self.brain.hidden_weights[index].len());
+            self.brain.hidden_weights[index][input] = self.params.weight.sample(self.rng);
+            let output = self.rng.gen_range(0..self.brain.output_weights.len());
+            self.brain.output_weights[output][index] = self.params.weight.sample(self.rng);
+        }
+    }
+}
+
+impl<'a> From<MutateNeural<'a>> for &'a NeuralBrain {
+    fn from(val: MutateNeural<'a>) -> Self {
+        val.into_inner()
+    }
+}
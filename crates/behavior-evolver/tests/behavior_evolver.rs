@@ -14,9 +14,11 @@ use creature_builder::{
         value::ExprValue,
         Expr,
     },
+    joint::CreatureJointId,
     sensor::{ContactFilter, ContactFilterTag},
     CreatureBuilderPlugin, CreatureId,
 };
+use data_structure_utils::graphs::directed::EdgeID;
 
 
 fn main() {
@@ -86,7 +88,7 @@ fn behavior_evolver_scene(mut commands: Commands, mut meshes: ResMut<Assets<Mesh
             )),
             Box::new(ExprNode::Value(CreatureContextElement::GlobalJoint {
                 element: JointContextElement::ChildContact { face: LimbAttachFace::PosY },
-                joint: 2,
+                joint: CreatureJointId { edge: EdgeID(4), instance: 0 },
             })),
         ),
     };
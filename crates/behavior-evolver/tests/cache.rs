@@ -0,0 +1,44 @@
+use behavior_evolver::{
+    evolution::{
+        cache::{morphology_hash, FitnessCache},
+        generation::TestResult,
+    },
+    mutate::RandomMorphologyParams,
+};
+use creature_builder::{builder::node::CreatureMorphologyGraph, CreatureId};
+
+
+#[test]
+fn hash() {
+    let mut rng = rand::thread_rng();
+    let morph = RandomMorphologyParams::default().build_morph(&mut rng, CreatureId(0));
+
+    // Copies hash the same whatever their id, even through serialization
+    let mut copy: CreatureMorphologyGraph = ron::de::from_str(&ron::ser::to_string(&morph).unwrap()).unwrap();
+    copy.creature = CreatureId(1);
+    assert_eq!(morphology_hash(&morph), morphology_hash(&copy));
+
+    copy.nodes_mut()[0].data.density += 1.0;
+    assert_ne!(morphology_hash(&morph), morphology_hash(&copy));
+}
+
+
+#[test]
+fn reevaluation() {
+    let mut cache = FitnessCache::new().with_reevaluation(2);
+    let record = |cache: &mut FitnessCache, generation: usize, fitness: f32| {
+        cache.record(7, generation, TestResult { fitness, objectives: vec![fitness], ..Default::default() }).fitness
+    };
+
+    assert!(cache.get(7, 0).is_none());
+    assert_eq!(record(&mut cache, 0, 1.0), 1.0);
+    assert_eq!(cache.get(7, 1).unwrap().fitness, 1.0);
+    assert!(cache.get(7, 2).is_none());
+
+    // Tests are averaged
+    assert_eq!(record(&mut cache, 2, 3.0), 2.0);
+    assert_eq!(cache.get(7, 3).unwrap().objectives, vec![2.0]);
+
+    cache.retain(&[8].into_iter().collect());
+    assert!(cache.get(7, 3).is_none());
+}
@@ -0,0 +1,78 @@
+use behavior_evolver::{
+    evolution::{
+        competition::{CompetitionArena, CompetitionMatch, Contestant, Tournament, TournamentOpponents},
+        terrain::Terrain,
+    },
+    mutate::RandomMorphologyParams,
+};
+use creature_builder::CreatureId;
+
+
+fn matches_played(matches: &[CompetitionMatch], creature: usize) -> usize {
+    matches.iter().flat_map(|m| m.sides).filter(|side| *side == Contestant::Creature(creature)).count()
+}
+
+
+#[test]
+fn round_robin() {
+    let mut rng = rand::thread_rng();
+    let params = RandomMorphologyParams::default();
+    let population: Vec<_> = (0..10).map(|i| params.build_morph(&mut rng, CreatureId(i))).collect();
+
+    for count in [2, 3, 4, 5] {
+        let matches = Tournament::new(TournamentOpponents::Sample(count)).schedule(&population, &mut rng);
+        for i in 0..population.len() {
+            assert_eq!(matches_played(&matches, i), count);
+        }
+        assert!(matches.iter().all(|m| m.sides[0] != m.sides[1]));
+        assert!(matches
+            .iter()
+            .enumerate()
+            .all(|(i, a)| matches[..i].iter().all(|b| a.sides != b.sides && a.sides != [b.sides[1], b.sides[0]])));
+    }
+
+    // With an odd population and an odd count, one creature plays once more
+    let matches = Tournament::new(TournamentOpponents::Sample(3)).schedule(&population[..9], &mut rng);
+    let mut played: Vec<_> = (0..9).map(|i| matches_played(&matches, i)).collect();
+    played.sort();
+    assert_eq!(played, [3, 3, 3, 3, 3, 3, 3, 3, 4]);
+
+    // Too few creatures for a sample, so everyone plays everyone once
+    let matches = Tournament::new(TournamentOpponents::Sample(8)).schedule(&population[..4], &mut rng);
+    assert_eq!(matches.len(), 6);
+}
+
+
+#[test]
+fn hall_of_fame() {
+    let mut rng = rand::thread_rng();
+    let params = RandomMorphologyParams::default();
+    let population: Vec<_> = (0..4).map(|i| params.build_morph(&mut rng, CreatureId(i))).collect();
+
+    let mut tournament = Tournament::new(TournamentOpponents::HallOfFame(2));
+    for creature in population.iter().skip(1) {
+        tournament.crown(creature);
+    }
+    assert_eq!(tournament.champions.len(), 2);
+
+    // Creature 3 is a champion, so it only plays the other one
+    let matches = tournament.schedule(&population, &mut rng);
+    assert!(matches.iter().all(|m| matches!(m.sides, [Contestant::Creature(_), Contestant::Champion(_)])));
+    assert_eq!(matches_played(&matches, 0), 2);
+    assert_eq!(matches_played(&matches, 3), 1);
+}
+
+
+#[test]
+fn arena_object() {
+    let arena = CompetitionArena::default();
+    assert_eq!(arena.object_transform(None).translation.y, arena.object_size);
+    assert_eq!(arena.object_transform(Some(&Terrain::Flat)).translation.y, arena.object_size);
+
+    // The object rests on the terrain, not inside it
+    let slope = Terrain::Slope { angle: 0.2, start: 3.0 };
+    assert!((slope.height_at(5.0, 0.0) - 2.0 * 0.2f32.tan()).abs() < 1e-4);
+    let stairs = Terrain::Stairs { step_height: 0.5, step_depth: 1.0, steps: 4 };
+    assert!((stairs.height_at(3.5, 0.0) - 1.0).abs() < 1e-4);
+    assert!(stairs.height_at(-1.0, 0.0).abs() < 1e-4);
+}
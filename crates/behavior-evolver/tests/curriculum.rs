@@ -0,0 +1,47 @@
+use behavior_evolver::evolution::curriculum::{Curriculum, CurriculumStage};
+
+
+fn stage(name: &str, until_generation: Option<usize>, until_fitness: Option<f32>) -> CurriculumStage {
+    CurriculumStage { name: name.to_string(), until_generation, until_fitness, ..Default::default() }
+}
+
+
+#[test]
+fn by_generation() {
+    let mut curriculum = Curriculum::new(vec![stage("crawl", Some(5), None), stage("walk", Some(10), None), stage("run", None, None)]);
+    assert!(!curriculum.advance(4, 100.0));
+    assert_eq!(curriculum.current, 0);
+
+    assert!(curriculum.advance(5, 0.0));
+    assert_eq!(curriculum.current_stage().unwrap().name, "walk");
+    assert!(!curriculum.advance(9, 0.0));
+    assert!(curriculum.advance(12, 0.0));
+    assert_eq!(curriculum.current_stage().unwrap().name, "run");
+
+    // The last stage is never left
+    assert!(!curriculum.advance(100, 100.0));
+    assert_eq!(curriculum.current, 2);
+}
+
+
+#[test]
+fn by_fitness() {
+    let mut curriculum =
+        Curriculum::new(vec![stage("crawl", Some(50), Some(2.0)), stage("walk", None, Some(5.0)), stage("run", None, None)]);
+    assert!(!curriculum.advance(1, 1.5));
+    assert!(curriculum.advance(2, 2.0));
+    assert_eq!(curriculum.current_stage().unwrap().name, "walk");
+
+    // A stage only advances one step at a time
+    assert!(curriculum.advance(3, 10.0));
+    assert_eq!(curriculum.current_stage().unwrap().name, "run");
+    assert!(!curriculum.advance(4, 10.0));
+}
+
+
+#[test]
+fn empty() {
+    let mut curriculum = Curriculum::default();
+    assert!(!curriculum.advance(10, 10.0));
+    assert!(curriculum.current_stage().is_none());
+}
@@ -0,0 +1,67 @@
+use behavior_evolver::evolution::{
+    fluid::FluidConfig,
+    terrain::Terrain,
+    write::{load_environment, train_path, write_environment},
+    EnvironmentConfig,
+};
+use bevy::{math::Vec3, transform::components::Transform};
+use bevy_rapier3d::dynamics::Velocity;
+
+
+#[test]
+fn drag() {
+    let fluid = FluidConfig { viscosity: 0.5 };
+    // The faces normal to x have an area of 1, those normal to y an area of 2
+    let limb = Transform::from_scale(Vec3::new(1.0, 0.5, 0.5));
+
+    let (force, torque) = fluid.drag(&limb, &Velocity::linear(Vec3::X * 2.0));
+    assert_eq!(force, Vec3::NEG_X);
+    assert_eq!(torque, Vec3::ZERO);
+
+    let (force, _) = fluid.drag(&limb, &Velocity::linear(Vec3::NEG_Y * 2.0));
+    assert_eq!(force, Vec3::Y * 2.0);
+
+    // Only the faces moving into the fluid are resisted
+    let (force, _) = fluid.drag(&limb, &Velocity::linear(Vec3::new(2.0, 0.0, -2.0)));
+    assert_eq!(force, Vec3::new(-1.0, 0.0, 2.0));
+}
+
+
+/// Runs `test` on a scratch session that is removed afterwards
+fn with_session(name: &str, test: impl FnOnce(&str)) {
+    let session = format!("test-{name}-{}", std::process::id());
+    test(&session);
+    std::fs::remove_dir_all(train_path(&session).session).unwrap();
+}
+
+
+#[test]
+fn round_trip() {
+    with_session("environment", |session| {
+        assert_eq!(load_environment(session), None);
+
+        let environment = EnvironmentConfig { substeps: 2, ground_friction: 0.5, ..EnvironmentConfig::default() }
+            .with_terrain(Terrain::from_name("hills").unwrap());
+        write_environment(session, &environment);
+        assert_eq!(load_environment(session), Some(environment));
+    });
+}
+
+
+#[test]
+fn legacy() {
+    with_session("legacy-environment", |session| {
+        let path = train_path(session).session.join("environment.ron");
+
+        std::fs::write(&path, "Ground(Stairs(step_height: 0.5, step_depth: 1.0, steps: 3))").unwrap();
+        let terrain = Terrain::Stairs { step_height: 0.5, step_depth: 1.0, steps: 3 };
+        assert_eq!(load_environment(session), Some(EnvironmentConfig::default().with_terrain(terrain)));
+
+        std::fs::write(&path, "Water((viscosity: 2.0))").unwrap();
+        let water = EnvironmentConfig { fluid: Some(FluidConfig { viscosity: 2.0 }), ..EnvironmentConfig::water() };
+        assert_eq!(load_environment(session), Some(water));
+
+        std::fs::write(&path, "Lava").unwrap();
+        assert_eq!(load_environment(session), None);
+    });
+}
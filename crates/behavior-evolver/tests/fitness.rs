@@ -0,0 +1,163 @@
+use behavior_evolver::evolution::{
+    diagnostics::{ExploitLimits, ExploitPenalty, PhysicsDiagnostics},
+    fitness::{
+        broken_limbs,
+        climb::ClimbFitnessEval,
+        composite::{CompositeFitnessEval, FitnessTerm},
+        directional::DirectionalWalkFitnessEval,
+        push::PushFitnessEval,
+        BlockEvalInput, EvolutionFitnessEval, FitnessEvalInput, PENALTY_FITNESS,
+    },
+    push::PushTask,
+    terrain::Terrain,
+};
+use bevy::{
+    math::{Quat, Vec2, Vec3},
+    transform::components::Transform,
+};
+use bevy_rapier3d::dynamics::Velocity;
+use creature_builder::{effector::CreatureEnergy, sensor::LimbCollisionType};
+
+
+fn input(transform: Transform, velocity: Velocity) -> FitnessEvalInput {
+    FitnessEvalInput { limbs: vec![(Transform::IDENTITY, Velocity::zero()), (transform, velocity)], test_time: 180, ..Default::default() }
+}
+
+
+#[test]
+fn broken() {
+    assert_eq!(broken_limbs(&input(Transform::from_xyz(1.0, 2.0, 3.0), Velocity::linear(Vec3::X * 10.0)), 500.0), None);
+
+    let exploded = Transform::from_xyz(f32::NAN, 0.0, 0.0);
+    assert_eq!(broken_limbs(&input(exploded, Velocity::zero()), 500.0), Some(PENALTY_FITNESS));
+    assert_eq!(broken_limbs(&input(Transform::IDENTITY, Velocity::angular(Vec3::Y * f32::INFINITY)), 500.0), Some(PENALTY_FITNESS));
+    assert_eq!(broken_limbs(&input(Transform::IDENTITY, Velocity::linear(Vec3::X * 600.0)), 500.0), Some(PENALTY_FITNESS));
+}
+
+
+#[test]
+fn exploits() {
+    let limits = ExploitLimits::new(ExploitPenalty::Penalize(2.0));
+    let clean = PhysicsDiagnostics { joint_separation: 0.01, ground_penetration: 0.02, energy_gain: 0.5 };
+    assert_eq!(limits.penalize(5.0, &clean), 5.0);
+
+    let exploit = PhysicsDiagnostics { joint_separation: 1.0, energy_gain: 100.0, ..clean };
+    assert_eq!(limits.violations(&exploit), 2);
+    assert_eq!(limits.penalize(5.0, &exploit), 1.0);
+    assert_eq!(ExploitLimits::new(ExploitPenalty::Disqualify).penalize(5.0, &exploit), PENALTY_FITNESS);
+    assert_eq!(clean.max(exploit), PhysicsDiagnostics { ground_penetration: 0.02, ..exploit });
+}
+
+
+#[test]
+fn contact_ratio() {
+    let mut faces = [LimbCollisionType::None; 6];
+    let mut input = FitnessEvalInput { contacts: vec![faces; 4], ..Default::default() };
+    assert_eq!(input.ground_contact_ratio(), 0.0);
+
+    faces[3] = LimbCollisionType::GroundCollision;
+    input.contacts[0] = faces;
+    input.contacts[1] = faces;
+    faces[3] = LimbCollisionType::SelfCollision;
+    input.contacts[2] = faces;
+    assert_eq!(input.ground_contact_ratio(), 0.5);
+}
+
+
+#[test]
+fn composite() {
+    assert_eq!(FitnessTerm::from_name("forward"), Some(FitnessTerm::DirectionalDisplacement));
+    assert_eq!(FitnessTerm::from_name("distance"), None);
+
+    let weights = CompositeFitnessEval::blend(&[(FitnessTerm::Displacement, 1.0), (FitnessTerm::EnergyUse, -0.5)]);
+    assert_eq!(weights, vec![0.0, 1.0, 0.0, 0.0, 0.0, -0.5, 0.0]);
+
+    let mut fitness = CompositeFitnessEval::default();
+    fitness.set_weights(&weights);
+    fitness.eval_start(input(Transform::IDENTITY, Velocity::zero()));
+    let moved = FitnessEvalInput {
+        limbs: vec![(Transform::from_xyz(3.0, 0.0, 4.0), Velocity::linear(Vec3::X))],
+        masses: vec![1.0],
+        energy: CreatureEnergy { work: 2.0, effort: 4.0 },
+        ..Default::default()
+    };
+    fitness.eval_continuous(moved.clone());
+    assert_eq!(fitness.final_eval(moved.clone()), 4.0);
+    assert_eq!(fitness.final_objectives(moved), vec![5.0, -1.0]);
+}
+
+
+#[test]
+fn directional() {
+    let mut fitness = DirectionalWalkFitnessEval::default();
+    fitness.set_heading(Vec2::Y * 2.0);
+    fitness.eval_start(input(Transform::IDENTITY, Velocity::zero()));
+
+    let turned = Transform::from_xyz(2.0, 0.0, 6.0).with_rotation(Quat::from_rotation_y(std::f32::consts::FRAC_PI_2));
+    let objectives = fitness.final_objectives(FitnessEvalInput { limbs: vec![(turned, Velocity::zero())], ..Default::default() });
+    assert_eq!(objectives[0], 6.0);
+    assert_eq!(objectives[1], -2.0);
+    assert!((objectives[2] + std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+
+    fitness.set_weights(&[1.0, 0.5, 0.0]);
+    let input = FitnessEvalInput { limbs: vec![(turned, Velocity::zero())], ..Default::default() };
+    assert_eq!(fitness.final_eval(input), 5.0);
+
+    // A root pitched to face straight up hasn't turned
+    let pitched = Transform::IDENTITY.with_rotation(Quat::from_rotation_x(std::f32::consts::FRAC_PI_2));
+    let objectives = fitness.final_objectives(FitnessEvalInput { limbs: vec![(pitched, Velocity::zero())], ..Default::default() });
+    assert_eq!(objectives[2], 0.0);
+}
+
+
+#[test]
+fn climb() {
+    let at = |height: f32, grounded: bool| {
+        let mut faces = [LimbCollisionType::None; 6];
+        if grounded {
+            faces[3] = LimbCollisionType::GroundCollision;
+        }
+        FitnessEvalInput {
+            limbs: vec![(Transform::from_xyz(0.0, height + 1.0, 0.0), Velocity::zero())],
+            contacts: vec![faces],
+            ..Default::default()
+        }
+    };
+
+    let mut jump = ClimbFitnessEval::default();
+    jump.eval_start(at(0.0, true));
+    [0.0, 3.0, 0.0, 0.0].into_iter().for_each(|height| jump.eval_continuous(at(height, true)));
+    assert_eq!(jump.final_eval(at(0.0, true)), 0.0);
+
+    let mut climb = ClimbFitnessEval::default();
+    climb.eval_start(at(0.0, true));
+    [0.5, 1.0, 1.5, 2.0, 1.75, 2.0, 2.5, 1.5].into_iter().for_each(|height| climb.eval_continuous(at(height, true)));
+    assert_eq!(climb.final_eval(at(1.5, true)), 1.5);
+    assert_eq!(climb.final_eval(at(1.5, false)), 0.0);
+}
+
+
+#[test]
+fn push() {
+    let at = |creature: Vec3, block: Vec3| FitnessEvalInput {
+        limbs: vec![(Transform::from_translation(creature), Velocity::zero())],
+        block: Some(BlockEvalInput { transform: Transform::from_translation(block), goal: Vec3::new(10.0, 0.0, 0.0) }),
+        ..Default::default()
+    };
+
+    let mut fitness = PushFitnessEval::default();
+    fitness.eval_start(at(Vec3::ZERO, Vec3::new(2.0, 0.5, 0.0)));
+    fitness.eval_continuous(at(Vec3::new(1.0, 0.0, 0.0), Vec3::new(2.0, 0.5, 0.0)));
+    fitness.eval_continuous(at(Vec3::new(4.0, 0.0, 0.0), Vec3::new(5.0, 0.5, 0.0)));
+    assert_eq!(fitness.final_objectives(at(Vec3::new(4.0, 0.0, 0.0), Vec3::new(5.0, 0.5, 0.0))), vec![3.0, -1.0]);
+
+    let mut unblocked = PushFitnessEval::default();
+    unblocked.eval_continuous(input(Transform::IDENTITY, Velocity::zero()));
+    assert_eq!(unblocked.final_eval(input(Transform::IDENTITY, Velocity::zero())), 0.0);
+
+    // The block rests on the terrain at its start
+    let task = PushTask::default();
+    assert_eq!(task.block_transform(None).translation, Vec3::new(2.0, 0.5, 0.0));
+    let slope = Terrain::Slope { angle: 0.2, start: 1.0 };
+    assert!((task.block_transform(Some(&slope)).translation.y - (0.5 + 0.2f32.tan())).abs() < 1e-4);
+}
@@ -0,0 +1,27 @@
+use behavior_evolver::{evolution::hall_of_fame::HallOfFame, mutate::RandomMorphologyParams};
+use creature_builder::CreatureId;
+
+
+#[test]
+fn keeps_fittest() {
+    let mut rng = rand::thread_rng();
+    let params = RandomMorphologyParams::default();
+    let creatures: Vec<_> = (0..4).map(|i| params.build_morph(&mut rng, CreatureId(i))).collect();
+
+    let mut hall_of_fame = HallOfFame::new(2);
+    assert!(hall_of_fame.insert(&creatures[0], 1.0, 0));
+    assert!(hall_of_fame.insert(&creatures[1], 3.0, 0));
+    assert!(hall_of_fame.insert(&creatures[2], 2.0, 1));
+    assert!(!hall_of_fame.insert(&creatures[3], 0.5, 1));
+    assert!(!hall_of_fame.insert(&creatures[3], f32::NAN, 1));
+
+    let ids: Vec<_> = hall_of_fame.entries.iter().map(|entry| entry.creature.creature.0).collect();
+    assert_eq!(ids, vec![1, 2]);
+
+    // A creature already in the hall keeps its best fitness
+    assert!(!hall_of_fame.insert(&creatures[2], 1.5, 2));
+    assert!(hall_of_fame.insert(&creatures[2], 4.0, 2));
+    assert_eq!(hall_of_fame.entries.len(), 2);
+    let best = hall_of_fame.best().unwrap();
+    assert_eq!((best.creature.creature.0, best.fitness, best.generation), (2, 4.0, 2));
+}
@@ -0,0 +1,52 @@
+use behavior_evolver::{
+    evolution::map_elites::{CreatureTrait, CreatureTraits, EliteDimension, MapElites},
+    mutate::RandomMorphologyParams,
+};
+use creature_builder::CreatureId;
+
+
+#[test]
+fn cells() {
+    let dimension = EliteDimension { feature: CreatureTrait::Height, min: 0.0, max: 4.0, bins: 8 };
+    let height = |height: f32| CreatureTraits { height, ..Default::default() };
+    assert_eq!(dimension.bin(&height(0.0)), 0);
+    assert_eq!(dimension.bin(&height(1.1)), 2);
+    assert_eq!(dimension.bin(&height(4.0)), 7);
+
+    // Values outside the range fall into the first or last cell
+    assert_eq!(dimension.bin(&height(-3.0)), 0);
+    assert_eq!(dimension.bin(&height(100.0)), 7);
+    assert_eq!(dimension.bin(&height(f32::NAN)), 0);
+
+    let map_elites = MapElites::new(vec![EliteDimension::new(CreatureTrait::LimbCount), dimension]);
+    assert_eq!(map_elites.cell(&CreatureTraits { limb_count: 5, height: 2.2, ..Default::default() }), vec![2, 4]);
+}
+
+
+#[test]
+fn insert() {
+    let mut rng = rand::thread_rng();
+    let creature = |id: usize, rng: &mut _| RandomMorphologyParams::default().build_morph(rng, CreatureId(id));
+    let mut map_elites = MapElites::new(vec![EliteDimension::new(CreatureTrait::Speed)]);
+    let slow = CreatureTraits { speed: 0.2, ..Default::default() };
+    let fast = CreatureTraits { speed: 4.2, ..Default::default() };
+
+    assert!(map_elites.random_elite(&mut rng).is_none());
+
+    assert!(map_elites.insert(&creature(0, &mut rng), 1.0, slow));
+    assert!(!map_elites.insert(&creature(1, &mut rng), 0.5, slow));
+    assert!(!map_elites.insert(&creature(2, &mut rng), f32::INFINITY, slow));
+    assert_eq!(map_elites.cells[&vec![0]].creature.creature, CreatureId(0));
+
+    // Fitter creatures replace the elite of their cell
+    assert!(map_elites.insert(&creature(3, &mut rng), 2.0, slow));
+    assert!(map_elites.insert(&creature(4, &mut rng), 0.1, fast));
+    assert_eq!(map_elites.cells.len(), 2);
+    assert_eq!(map_elites.cells[&vec![0]].creature.creature, CreatureId(3));
+    assert_eq!(map_elites.cells[&vec![8]].fitness, 0.1);
+
+    for _ in 0..20 {
+        let elite = map_elites.random_elite(&mut rng).unwrap();
+        assert!([CreatureId(3), CreatureId(4)].contains(&elite.creature.creature));
+    }
+}
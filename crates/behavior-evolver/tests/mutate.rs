@@ -1,8 +1,11 @@
+use std::collections::HashSet;
+
 use behavior_evolver::mutate::{
     edge::{MutateEdge, MutateEdgeParams},
     expr::{MutateExpr, MutateExprParams, RandomExprParams},
+    neural::{MutateNeural, MutateNeuralParams, RandomNeuralParams},
     node::{MutateNode, MutateNodeParams},
-    MutateFieldParams, MutateMorphology, MutateMorphologyParams, RandomMorphologyParams,
+    CreatureControllerType, MutateFieldParams, MutateMorphology, MutateMorphologyParams, RandomMorphologyParams,
 };
 use bevy::math::{Quat, Vec2, Vec3};
 use bevy_rapier3d::dynamics::JointAxesMask;
@@ -11,7 +14,9 @@ use creature_builder::{
         node::{LimbConnection, LimbNode},
         placement::{LimbAttachFace, LimbRelativePlacement},
     },
-    effector::CreatureJointEffectors,
+    effector::{CreatureContextElement, CreatureJointEffectors},
+    expr::node::ExprNode,
+    neural::NeuralBrain,
     CreatureId,
 };
 
@@ -93,6 +98,88 @@ fn expr() {
 }
 
 
+#[test]
+fn neural() {
+    let mut rng = rand::thread_rng();
+    let mut brain = RandomNeuralParams::default().build_brain(&mut rng);
+    let mut params = MutateNeuralParams::default();
+
+    let mut mutate = MutateNeural::new(&mut brain, &mut rng, &mut params);
+
+    for _ in 0..1000 {
+        mutate.mutate();
+        let brain = mutate.inner();
+        assert!(brain.hidden_len() >= 1);
+        assert!(brain.hidden_weights.iter().all(|x| x.len() == brain.hidden_len() + NeuralBrain::INPUTS));
+        assert!(brain.output_weights.iter().all(|x| x.len() == brain.hidden_len()));
+    }
+}
+
+
+#[test]
+fn neural_morph() {
+    let mut rng = rand::thread_rng();
+    let rand_params = RandomMorphologyParams { controller: CreatureControllerType::Neural, ..Default::default() };
+
+    for _ in 0..100 {
+        let mut morph = rand_params.build_morph(&mut rng, CreatureId(0));
+        let mut params = MutateMorphologyParams::default();
+
+        let mut mutate = MutateMorphology::new(&mut morph, &mut rng, &mut params);
+
+        for _ in 0..100 {
+            mutate.mutate();
+        }
+        assert!(morph.brain.is_some());
+        assert!(morph.edges().iter().all(|x| x.data.effectors.effectors.iter().all(|x| x.is_none())));
+    }
+}
+
+
+#[test]
+fn central_nodes() {
+    fn reads_local_joint(node: &ExprNode) -> bool {
+        match node {
+            ExprNode::Value(value) => matches!(value, CreatureContextElement::LocalJoint { .. }),
+            ExprNode::Constant(_) => false,
+            ExprNode::UnaryOp(_, n) => reads_local_joint(n),
+            ExprNode::BinaryOp(_, n1, n2) => reads_local_joint(n1) || reads_local_joint(n2),
+            ExprNode::TernaryOp(_, n1, n2, n3) => reads_local_joint(n1) || reads_local_joint(n2) || reads_local_joint(n3),
+        }
+    }
+
+    let mut rng = rand::thread_rng();
+    let rand_params = RandomMorphologyParams { central: 3..6, ..Default::default() };
+
+    for _ in 0..100 {
+        let mut morph = rand_params.build_morph(&mut rng, CreatureId(0));
+        let mut params = MutateMorphologyParams { central_add_freq: 0.5, ..Default::default() };
+
+        let mut mutate = MutateMorphology::new(&mut morph, &mut rng, &mut params);
+
+        // Central nodes aren't evaluated for any one joint
+        for _ in 0..100 {
+            mutate.mutate();
+        }
+        assert!(morph.central.iter().all(|expr| !reads_local_joint(&expr.root)));
+    }
+}
+
+
+#[test]
+fn joint_ids() {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..100 {
+        let morph = RandomMorphologyParams::default().build_morph(&mut rng, CreatureId(0));
+        let ids = morph.joint_ids();
+        let unique: HashSet<_> = ids.iter().copied().collect();
+        assert_eq!(ids.len(), unique.len());
+        assert_eq!(ids, morph.joint_ids());
+    }
+}
+
+
 #[test]
 fn morph() {
     let mut rng = rand::thread_rng();
@@ -0,0 +1,21 @@
+use behavior_evolver::evolution::novelty::{BehaviorDescriptor, NoveltySearch};
+
+
+#[test]
+fn scores() {
+    let behaviors = vec![BehaviorDescriptor(vec![0.0, 0.0]), BehaviorDescriptor(vec![1.0, 0.0]), BehaviorDescriptor(vec![10.0, 0.0])];
+    let novelty = NoveltySearch { k: 1, ..Default::default() };
+    assert_eq!(novelty.scores(&behaviors), vec![1.0, 1.0, 9.0]);
+
+    let novelty = NoveltySearch { k: 1, archive: vec![BehaviorDescriptor(vec![10.0, 1.0])], ..Default::default() };
+    assert_eq!(novelty.scores(&behaviors)[2], 1.0);
+}
+
+
+#[test]
+fn archive() {
+    let behaviors = vec![BehaviorDescriptor(vec![0.0]), BehaviorDescriptor(vec![1.0]), BehaviorDescriptor(vec![2.0])];
+    let mut novelty = NoveltySearch { archive_threshold: 1.0, archive_size: 1, ..Default::default() };
+    novelty.update_archive(&behaviors, &[0.5, 1.0, 3.0]);
+    assert_eq!(novelty.archive, vec![BehaviorDescriptor(vec![2.0])]);
+}
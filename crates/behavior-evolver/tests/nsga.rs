@@ -0,0 +1,31 @@
+use behavior_evolver::evolution::nsga::{crowding_distance, dominates, non_dominated_sort, nsga2_order};
+
+
+#[test]
+fn domination() {
+    assert!(dominates(&[1.0, 1.0], &[0.0, 1.0]));
+    assert!(!dominates(&[1.0, 1.0], &[1.0, 1.0]));
+    assert!(!dominates(&[1.0, 0.0], &[0.0, 1.0]));
+}
+
+
+#[test]
+fn fronts() {
+    let objectives = vec![vec![0.0, 0.0], vec![2.0, 1.0], vec![1.0, 2.0], vec![1.0, 1.0], vec![1.5, 1.5]];
+    let mut fronts = non_dominated_sort(&objectives);
+    fronts.iter_mut().for_each(|front| front.sort());
+    assert_eq!(fronts, vec![vec![1, 2, 4], vec![3], vec![0]]);
+}
+
+
+#[test]
+fn crowding() {
+    let objectives = vec![vec![0.0, 4.0], vec![1.0, 3.5], vec![3.0, 1.0], vec![4.0, 0.0]];
+    let distances = crowding_distance(&objectives, &[0, 1, 2, 3]);
+    assert!(distances[0].is_infinite() && distances[3].is_infinite());
+    assert!(distances[2] > distances[1]);
+
+    let order = nsga2_order(&objectives);
+    assert_eq!(order.len(), 4);
+    assert_eq!(&order[2..], &[2, 1]);
+}
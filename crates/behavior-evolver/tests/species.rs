@@ -0,0 +1,32 @@
+use behavior_evolver::evolution::species::{expr_distance, Speciation};
+use creature_builder::expr::{
+    node::{ExprBinaryOp, ExprNode, ExprUnaryOp},
+    value::ExprValue,
+};
+
+
+#[test]
+fn quotas() {
+    // Species 1 has the same total fitness as species 0 but twice the members
+    let quotas = Speciation::quotas(&[0, 1, 1, 2], &[4.0, 2.0, 2.0, 0.0], 10);
+    assert_eq!(quotas.iter().map(|(_, quota)| quota).sum::<usize>(), 10);
+    assert_eq!(quotas[0].0, 0);
+    assert!(quotas[0].1 > quotas[1].1);
+    assert_eq!(quotas[2], (2, 0));
+
+    let quotas = Speciation::quotas(&[0, 1, 2], &[1.0, 1.0, 1.0], 7);
+    assert_eq!(quotas.iter().map(|(_, quota)| quota).sum::<usize>(), 7);
+}
+
+
+#[test]
+fn expressions() {
+    let constant = |v: f32| Box::new(ExprNode::Constant(ExprValue(v)));
+    let a = ExprNode::BinaryOp(ExprBinaryOp::Add, constant(1.0), constant(2.0));
+    let b = ExprNode::BinaryOp(ExprBinaryOp::Mul, constant(1.0), constant(2.0));
+    let c = ExprNode::UnaryOp(ExprUnaryOp::Sin, constant(1.0));
+
+    assert_eq!(expr_distance(&a, &a), 0.0);
+    assert_eq!(expr_distance(&a, &b), 1.0);
+    assert_eq!(expr_distance(&a, &c), 3.0);
+}
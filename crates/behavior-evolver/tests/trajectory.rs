@@ -0,0 +1,68 @@
+use behavior_evolver::evolution::{
+    fitness::FitnessEvalInput,
+    trajectory::{Trajectory, TrajectoryRecorder},
+};
+use bevy::{
+    math::{Quat, Vec3},
+    transform::components::Transform,
+};
+use bevy_rapier3d::dynamics::Velocity;
+
+
+fn at(step: usize, x: f32, turn: f32) -> FitnessEvalInput {
+    let transform = Transform::from_xyz(x, 1.0, 0.0).with_rotation(Quat::from_rotation_y(turn));
+    FitnessEvalInput { limbs: vec![(transform, Velocity::linear(Vec3::X * 2.0))], step, ..Default::default() }
+}
+
+
+#[test]
+fn recording() {
+    let mut recorder = TrajectoryRecorder::new(2);
+    (1..=6).for_each(|step| recorder.record(&at(step, step as f32, 0.0)));
+    recorder.finish_trial(7, 0, 1.5);
+    recorder.record(&at(2, 0.0, 0.0));
+    recorder.finish_trial(7, 1, 0.5);
+
+    let trajectories = recorder.take();
+    assert_eq!(trajectories.len(), 2);
+    assert_eq!(trajectories[0].steps.iter().map(|step| step.step).collect::<Vec<_>>(), vec![2, 4, 6]);
+    assert_eq!((trajectories[1].trial, trajectories[1].steps.len()), (1, 1));
+    assert!(recorder.take().is_empty());
+
+    let csv = Trajectory::to_csv(&trajectories);
+    assert_eq!(csv.lines().count(), 5);
+    assert_eq!(Trajectory::from_csv(&csv), Some(trajectories));
+    assert_eq!(Trajectory::from_csv("header\n1,2,3\n"), None);
+}
+
+
+#[test]
+fn match_sides() {
+    let mut recorder = TrajectoryRecorder::new(1);
+    for step in 1..=3 {
+        recorder.record_side(0, &at(step, step as f32, 0.0));
+        recorder.record_side(1, &at(step, -(step as f32), 0.0));
+    }
+    recorder.finish_side(1, 4, 0, -1.0);
+    recorder.finish_side(0, 3, 0, 1.0);
+
+    let trajectories = recorder.take();
+    assert_eq!(trajectories.iter().map(|trajectory| trajectory.creature).collect::<Vec<_>>(), vec![4, 3]);
+    assert_eq!(trajectories[0].steps[2].center.x, -3.0);
+    assert_eq!(trajectories[1].steps[2].center.x, 3.0);
+}
+
+
+#[test]
+fn summary() {
+    let mut recorder = TrajectoryRecorder::new(1);
+    recorder.record(&at(1, 0.0, 0.0));
+    recorder.record(&at(2, 3.0, std::f32::consts::FRAC_PI_2));
+    recorder.finish_trial(0, 0, 3.0);
+
+    let summary = recorder.take()[0].summary();
+    assert_eq!(summary.displacement, 3.0);
+    assert!(summary.max_height.abs() < 1e-5);
+    assert_eq!(summary.mean_speed, 2.0);
+    assert!((summary.turn - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+}
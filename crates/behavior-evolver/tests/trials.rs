@@ -0,0 +1,37 @@
+use behavior_evolver::evolution::{
+    terrain::Terrain,
+    trials::{TrialAggregation, TrialPerturbation},
+};
+
+
+#[test]
+fn aggregation() {
+    let scores = [4.0, 1.0, 2.0, 5.0];
+    assert_eq!(TrialAggregation::Mean.aggregate(&scores), 3.0);
+    assert_eq!(TrialAggregation::Min.aggregate(&scores), 1.0);
+    assert_eq!(TrialAggregation::Median.aggregate(&scores), 3.0);
+    assert_eq!(TrialAggregation::Median.aggregate(&scores[..3]), 2.0);
+
+    // The scores are 1 and 3, with a mean of 2 and a standard deviation of 1
+    assert_eq!(TrialAggregation::MeanMinusStdDev(2.0).aggregate(&[1.0, 3.0]), 0.0);
+    assert_eq!(TrialAggregation::from_name("std:2"), Some(TrialAggregation::MeanMinusStdDev(2.0)));
+    assert_eq!(TrialAggregation::from_name("max"), None);
+}
+
+
+#[test]
+fn perturbation() {
+    let mut rng = rand::thread_rng();
+    let perturbation = TrialPerturbation::new(0.5, 2.0);
+    for _ in 0..100 {
+        let (offset, turn) = perturbation.sample(&mut rng);
+        assert!(offset.y == 0.0 && offset.length() <= 2.0);
+        assert!(turn.abs() <= 0.5);
+    }
+    assert_eq!(TrialPerturbation::default().sample(&mut rng), (Default::default(), 0.0));
+
+    let hills = Terrain::from_name("hills").unwrap();
+    assert_eq!(hills.reseeded(0), hills);
+    assert_ne!(hills.reseeded(1), hills);
+    assert_eq!(Terrain::Flat.reseeded(1), Terrain::Flat);
+}
@@ -12,7 +12,12 @@ use data_structure_utils::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    builder::placement::LimbRelativePlacement, effector::CreatureJointEffectors, joint::CreatureJointBuilder, limb::CreatureLimbBundle,
+    builder::placement::LimbRelativePlacement,
+    effector::{CreatureCentralNodes, CreatureEnergy, CreatureJointEffectors},
+    expr::Expr,
+    joint::{CreatureJointBuilder, CreatureJointId, CreatureJointStrength},
+    limb::CreatureLimbBundle,
+    neural::NeuralBrain,
     CreatureId,
 };
 
@@ -48,7 +53,7 @@ impl NodeData<LimbConnection, BuildResult, BuildParameters> for LimbNode {
         from_node: Option<&Self>,
         from_edge: Option<&LimbConnection>,
         from_node_id: NodeID,
-        _from_edge_id: EdgeID,
+        from_edge_id: EdgeID,
     ) -> bool {
         match (from_node, from_edge) {
             (Some(_prev_node), Some(edge)) => {
@@ -70,6 +75,10 @@ impl NodeData<LimbConnection, BuildResult, BuildParameters> for LimbNode {
                 let prev_limb_id = result.node_limb_ids.get(&from_node_id).unwrap().peek().unwrap();
                 let cur_limb_id = result.current_limb_id;
                 if should_spawn {
+                    let instance = result.edge_instances.entry(from_edge_id).or_insert(0);
+                    let joint_id = CreatureJointId { edge: from_edge_id, instance: *instance };
+                    *instance += 1;
+
                     result.limb_build_queue.push_back((
                         CreatureLimbBundle::new()
                             .with_transform(limb_position.transform.with_scale(Vec3::ONE))
@@ -98,7 +107,8 @@ impl NodeData<LimbConnection, BuildResult, BuildParameters> for LimbNode {
                                     .local_basis1(prev_transform.rotation.inverse() * limb_position.transform.rotation)
                                     .build(),
                             )
-                            .with_effectors(edge.effectors.clone()),
+                            .with_effectors(edge.effectors.clone())
+                            .with_id(joint_id),
                         cur_limb_id,
                         prev_limb_id,
                     ));
@@ -196,8 +206,11 @@ pub struct BuildResult {
     recursive_limits: HashMap<NodeID, usize>,
     transforms: HashMap<NodeID, Stack<Transform>>,
     node_limb_ids: HashMap<NodeID, Stack<usize>>,
+    edge_instances: HashMap<EdgeID, usize>,
     current_limb_id: usize,
     creature_id: CreatureId,
+    brain: Option<NeuralBrain>,
+    central: Vec<Expr>,
 }
 
 impl DirectedGraphResult for BuildResult {
@@ -209,7 +222,10 @@ impl DirectedGraphResult for BuildResult {
             transforms: HashMap::new(),
             current_limb_id: 0,
             node_limb_ids: HashMap::new(),
+            edge_instances: HashMap::new(),
             creature_id: CreatureId(0),
+            brain: None,
+            central: Vec::new(),
         }
     }
 }
@@ -253,21 +269,60 @@ impl BuildResult {
         self.limb_build_queue.iter_mut().for_each(|x| x.0.transform.translation.y -= mini - 0.1);
     }
 
+    /// Turns the creature about the vertical axis through its center and moves
+    /// its center to the given point, raising it by the point's height
+    pub fn place(&mut self, position: Vec3, turn: f32) {
+        self.ensure_nonempty();
+
+        let center = self.horizontal_center();
+        let rotation = Quat::from_rotation_y(turn);
+        self.limb_build_queue.iter_mut().for_each(|limb| {
+            let transform = &mut limb.0.transform;
+            transform.translation = rotation * (transform.translation - center) + position;
+            transform.rotation = rotation * transform.rotation;
+        });
+    }
+
+    /// Turns the creature about the vertical axis through its center and moves
+    /// it by the given offset
+    pub fn nudge(&mut self, offset: Vec3, turn: f32) {
+        self.ensure_nonempty();
+
+        let center = self.horizontal_center();
+        self.place(center + offset, turn);
+    }
+
+    /// The mean position of the limbs, projected onto the ground plane
+    fn horizontal_center(&self) -> Vec3 {
+        let total: Vec3 = self.limb_build_queue.iter().map(|limb| limb.0.transform.translation).sum();
+        (total / self.limb_build_queue.len() as f32) * Vec3::new(1.0, 0.0, 1.0)
+    }
+
     pub fn build_nowindow(&mut self, commands: &mut Commands) {
         self.ensure_nonempty();
 
         let mut entity_ids = HashMap::new();
+        let limbs: HashMap<usize, &CreatureLimbBundle> = self.limb_build_queue.iter().map(|(limb, index)| (*index, limb)).collect();
+        let strengths: Vec<_> =
+            self.joint_build_queue.iter().map(|joint| CreatureJointStrength::between(limbs[&joint.2], limbs[&joint.1])).collect();
         let limb_count = self.limb_build_queue.len();
         while let Some(limb) = self.limb_build_queue.pop_front() {
             let id = commands.spawn(limb.0.with_creature(self.creature_id).with_limb_count(limb_count)).id();
             entity_ids.insert(limb.1, id);
         }
+        commands.entity(entity_ids[&0]).insert((CreatureCentralNodes { nodes: self.central.clone() }, CreatureEnergy::default()));
 
-        while let Some(joint) = self.joint_build_queue.pop_front() {
+        for (joint, strength) in self.joint_build_queue.drain(..).zip(strengths) {
             let parent = entity_ids.get(&joint.2).unwrap();
-            commands
-                .entity(*entity_ids.get(&joint.1).unwrap())
-                .insert(joint.0.with_parent(*parent).with_creature(self.creature_id).finish());
+            commands.entity(*entity_ids.get(&joint.1).unwrap()).insert(
+                joint
+                    .0
+                    .with_parent(*parent)
+                    .with_creature(self.creature_id)
+                    .with_strength(strength)
+                    .with_brain(self.brain.clone())
+                    .finish(),
+            );
         }
     }
 
@@ -281,6 +336,9 @@ impl BuildResult {
         self.ensure_nonempty();
 
         let mut entity_ids = HashMap::new();
+        let limbs: HashMap<usize, &CreatureLimbBundle> = self.limb_build_queue.iter().map(|(limb, index)| (*index, limb)).collect();
+        let strengths: Vec<_> =
+            self.joint_build_queue.iter().map(|joint| CreatureJointStrength::between(limbs[&joint.2], limbs[&joint.1])).collect();
         let limb_count = self.limb_build_queue.len();
         while let Some(limb) = self.limb_build_queue.pop_front() {
             let id = commands
@@ -288,12 +346,19 @@ impl BuildResult {
                 .id();
             entity_ids.insert(limb.1, id);
         }
+        commands.entity(entity_ids[&0]).insert((CreatureCentralNodes { nodes: self.central.clone() }, CreatureEnergy::default()));
 
-        while let Some(joint) = self.joint_build_queue.pop_front() {
+        for (joint, strength) in self.joint_build_queue.drain(..).zip(strengths) {
             let parent = entity_ids.get(&joint.2).unwrap();
-            commands
-                .entity(*entity_ids.get(&joint.1).unwrap())
-                .insert(joint.0.with_parent(*parent).with_creature(self.creature_id).finish());
+            commands.entity(*entity_ids.get(&joint.1).unwrap()).insert(
+                joint
+                    .0
+                    .with_parent(*parent)
+                    .with_creature(self.creature_id)
+                    .with_strength(strength)
+                    .with_brain(self.brain.clone())
+                    .finish(),
+            );
         }
     }
 }
@@ -312,11 +377,19 @@ pub struct CreatureMorphologyGraph {
     pub graph: DirectedGraph<LimbNode, LimbConnection, BuildResult, BuildParameters>,
     pub root: Transform,
     pub creature: CreatureId,
+    /// The neural controller shared by every joint, used instead of the
+    /// expression effectors when set
+    #[serde(default)]
+    pub brain: Option<NeuralBrain>,
+    /// Expressions evaluated once per step for the whole creature, whose
+    /// outputs can be read by any effector
+    #[serde(default)]
+    pub central: Vec<Expr>,
 }
 
 impl CreatureMorphologyGraph {
     pub fn new(creature: CreatureId) -> Self {
-        Self { graph: DirectedGraph::new(), root: Transform::IDENTITY, creature }
+        Self { graph: DirectedGraph::new(), root: Transform::IDENTITY, creature, brain: None, central: Vec::new() }
     }
 
     pub fn add_node(&mut self, node: LimbNode) -> NodeID {
@@ -382,6 +455,13 @@ impl CreatureMorphologyGraph {
     pub fn evaluate(&self) -> BuildResult {
         let mut res = self.graph.evaluate(BuildParameters { root_transform: self.root });
         res.creature_id = self.creature;
+        res.brain = self.brain.clone();
+        res.central = self.central.clone();
         res
     }
+
+    /// The identifiers of every joint that is built from this graph
+    pub fn joint_ids(&self) -> Vec<CreatureJointId> {
+        self.evaluate().joint_build_queue.iter().map(|(joint, _, _)| joint.id()).collect()
+    }
 }
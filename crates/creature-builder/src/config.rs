@@ -17,12 +17,14 @@ bitflags::bitflags! {
         const NONE = 0;
         const LIMB_VS_LIMB = 1 << 0;
         const LIMB_VS_GROUND = 1 << 1;
-        const ALL = Self::LIMB_VS_LIMB.bits() | Self::LIMB_VS_GROUND.bits();
+        /// Limbs of different creatures
+        const LIMB_VS_CREATURE = 1 << 2;
+        const ALL = Self::LIMB_VS_LIMB.bits() | Self::LIMB_VS_GROUND.bits() | Self::LIMB_VS_CREATURE.bits();
     }
 }
 
 impl Default for ActiveCollisionTypes {
     fn default() -> Self {
-        Self::LIMB_VS_GROUND
+        Self::LIMB_VS_GROUND | Self::LIMB_VS_CREATURE
     }
 }
@@ -5,12 +5,14 @@ use bevy::{
     math::{Quat, Vec3},
     transform::components::Transform,
 };
-use bevy_rapier3d::dynamics::JointAxis;
+use bevy_rapier3d::dynamics::{JointAxesMask, JointAxis};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     builder::placement::LimbAttachFace,
     expr::Expr,
+    joint::CreatureJointId,
+    neural::NeuralBrain,
     sensor::{LimbCollisionSensor, LimbCollisionType},
 };
 
@@ -19,11 +21,44 @@ use crate::{
 pub struct CreatureJointEffectors {
     /// Ordered: [X, Y, Z, AngX, AngY, AngZ]
     pub effectors: [Option<CreatureJointEffector>; 6],
+    /// The creature's neural controller, which replaces `effectors` when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub brain: Option<NeuralBrain>,
+    /// The clamped force applied along each axis in the last step, zero for
+    /// axes that weren't actuated.
+    /// Ordered: [X, Y, Z, AngX, AngY, AngZ]
+    #[serde(skip)]
+    pub outputs: [f32; 6],
 }
 
 impl CreatureJointEffectors {
     pub fn new(effectors: [Option<CreatureJointEffector>; 6]) -> Self {
-        Self { effectors }
+        Self { effectors, brain: None, outputs: [0.0; 6] }
+    }
+
+    /// Evaluates the force applied along each axis of the joint, skipping axes
+    /// that have no effector or are locked.
+    ///
+    /// Ordered: [X, Y, Z, AngX, AngY, AngZ]
+    pub fn evaluate(&mut self, context: &CreatureContext, locked_axes: JointAxesMask) -> [Option<f32>; 6] {
+        let mut forces = [None; 6];
+        match self.brain.as_mut() {
+            Some(brain) => {
+                let outputs = brain.evaluate(context);
+                for (i, force) in forces.iter_mut().enumerate() {
+                    if !locked_axes.contains(JointAxesMask::from_bits(1 << i).unwrap()) {
+                        *force = Some(outputs[i]);
+                    }
+                }
+            },
+            None => {
+                for (force, effector) in forces.iter_mut().zip(self.effectors.iter()) {
+                    let Some(effector) = effector else { continue };
+                    *force = Some(effector.expr.evaluate(context).0);
+                }
+            },
+        }
+        forces
     }
 
     pub fn insert(&mut self, effector: CreatureJointEffector, axis: JointAxis) {
@@ -45,16 +80,48 @@ pub struct CreatureJointEffector {
 }
 
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// A creature-wide set of expressions that are evaluated once per step,
+/// independent of any joint, and whose outputs every effector can read.
+///
+/// Each node may read the outputs of the nodes before it.
+#[derive(Component, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CreatureCentralNodes {
+    pub nodes: Vec<Expr>,
+}
+
+impl CreatureCentralNodes {
+    pub fn evaluate(&self, context: &mut CreatureContext) {
+        context.central.clear();
+        for node in self.nodes.iter() {
+            let value = node.evaluate(context).0;
+            context.central.push(value);
+        }
+    }
+}
+
+
+/// The total actuation performed by a creature's effectors since it was built
+#[derive(Component, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct CreatureEnergy {
+    /// The sum of `|torque · relative angular velocity|` over every joint
+    pub work: f32,
+    /// The sum of `|torque|` over every joint
+    pub effort: f32,
+}
+
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum CreatureContextElement {
     LocalJoint { element: JointContextElement },
-    GlobalJoint { element: JointContextElement, joint: usize },
+    GlobalJoint { element: JointContextElement, joint: CreatureJointId },
+    Central { index: usize },
     Time,
 }
 
 pub struct CreatureContext {
-    joints: Vec<JointContext>,
-    current_joint: usize,
+    joints: HashMap<CreatureJointId, JointContext>,
+    central: Vec<f32>,
+    current_joint: Option<CreatureJointId>,
     elapsed_time: f32,
 }
 
@@ -66,15 +133,15 @@ impl Default for CreatureContext {
 
 impl CreatureContext {
     pub fn new() -> Self {
-        Self { joints: Vec::new(), current_joint: 0, elapsed_time: 0.0 }
+        Self { joints: HashMap::new(), central: Vec::new(), current_joint: None, elapsed_time: 0.0 }
     }
 
-    pub fn add_joint(&mut self, ctx: JointContext) {
-        self.joints.push(ctx);
+    pub fn add_joint(&mut self, id: CreatureJointId, ctx: JointContext) {
+        self.joints.insert(id, ctx);
     }
 
-    pub fn set_current_joint(&mut self, index: usize) {
-        self.current_joint = index;
+    pub fn set_current_joint(&mut self, id: CreatureJointId) {
+        self.current_joint = Some(id);
     }
 
     pub fn len(&self) -> usize {
@@ -91,24 +158,49 @@ impl CreatureContext {
 
     pub fn index(&self, index: CreatureContextElement) -> Option<f32> {
         match index {
-            CreatureContextElement::LocalJoint { element } => Some(self.joints[self.current_joint][element]),
+            CreatureContextElement::LocalJoint { element } => {
+                let ctx = self.joints.get(&self.current_joint?)?;
+                Some(ctx[element])
+            },
             CreatureContextElement::GlobalJoint { element, joint } => {
-                let ctx = self.joints.get(joint)?;
+                let ctx = self.joints.get(&joint)?;
                 Some(ctx[element])
             },
+            CreatureContextElement::Central { index } => self.central.get(index).copied(),
             CreatureContextElement::Time => Some(self.elapsed_time),
         }
     }
 }
 
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum JointContextElement {
     ParentContact { face: LimbAttachFace },
     ChildContact { face: LimbAttachFace },
     JointAxis { axis: JointAxis },
 }
 
+impl JointContextElement {
+    /// The number of distinct sensors available to a joint
+    pub const COUNT: usize = 18;
+
+    pub fn from_index(index: usize) -> Self {
+        match index {
+            0..=5 => Self::ParentContact { face: LimbAttachFace::from_index(index) },
+            6..=11 => Self::ChildContact { face: LimbAttachFace::from_index(index - 6) },
+            12 => Self::JointAxis { axis: JointAxis::X },
+            13 => Self::JointAxis { axis: JointAxis::Y },
+            14 => Self::JointAxis { axis: JointAxis::Z },
+            15 => Self::JointAxis { axis: JointAxis::AngX },
+            16 => Self::JointAxis { axis: JointAxis::AngY },
+            17 => Self::JointAxis { axis: JointAxis::AngZ },
+            _ => {
+                panic!("Cannot index into JointContextElement with index {}", index)
+            },
+        }
+    }
+}
+
 
 #[derive(Clone)]
 pub struct JointContext {
@@ -124,14 +216,8 @@ impl JointContext {
         parent_transform: &Transform,
         child_transform: &Transform,
     ) -> Self {
-        let joint_axes = [
-            0.0,
-            0.0,
-            0.0,
-            Self::calc_basis_diff(parent_transform, child_transform, Vec3::X),
-            Self::calc_basis_diff(parent_transform, child_transform, Vec3::Y),
-            Self::calc_basis_diff(parent_transform, child_transform, Vec3::Z),
-        ];
+        let angles = Self::angles(parent_transform, child_transform);
+        let joint_axes = [0.0, 0.0, 0.0, angles.x, angles.y, angles.z];
         Self {
             parent_contacts: LimbCollisionSensor { faces: parent_contacts.faces, entities: HashMap::new() },
             child_contacts: LimbCollisionSensor { faces: child_contacts.faces, entities: HashMap::new() },
@@ -139,6 +225,16 @@ impl JointContext {
         }
     }
 
+    /// The angle between the child limb and its parent about each axis of the
+    /// child
+    pub fn angles(parent_transform: &Transform, child_transform: &Transform) -> Vec3 {
+        Vec3::new(
+            Self::calc_basis_diff(parent_transform, child_transform, Vec3::X),
+            Self::calc_basis_diff(parent_transform, child_transform, Vec3::Y),
+            Self::calc_basis_diff(parent_transform, child_transform, Vec3::Z),
+        )
+    }
+
     fn calc_basis_diff(parent_transform: &Transform, child_transform: &Transform, axis: Vec3) -> f32 {
         let axis1 = child_transform.rotation * axis;
         let twist_1 = Self::quat_swing_twist(parent_transform.rotation, axis1).0;
@@ -155,6 +251,7 @@ impl JointContext {
     /// `rotation = swing * twist`
     ///
     /// From: https://stackoverflow.com/a/22401169
+    #[allow(clippy::doc_lazy_continuation)]
     fn quat_swing_twist(quat: Quat, dir: Vec3) -> (Quat, Quat) {
         let ra = Vec3::new(quat.x, quat.y, quat.z);
         let p = dir * ra.dot(dir);
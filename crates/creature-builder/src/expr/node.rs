@@ -7,7 +7,7 @@ use crate::{
 };
 
 
-#[derive(Clone, Debug, RandField, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, RandField, Serialize, Deserialize)]
 pub enum ExprUnaryOp {
     Sign,
     Abs,
@@ -18,7 +18,7 @@ pub enum ExprUnaryOp {
     Sigmoid,
 }
 
-#[derive(Clone, Debug, RandField, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, RandField, Serialize, Deserialize)]
 pub enum ExprBinaryOp {
     Add,
     Sub,
@@ -31,7 +31,7 @@ pub enum ExprBinaryOp {
     Atan,
 }
 
-#[derive(Clone, Debug, RandField, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, RandField, Serialize, Deserialize)]
 pub enum ExprTernaryOp {
     IfElse,
     Lerp,
@@ -1,15 +1,52 @@
 use bevy::prelude::*;
 use bevy_rapier3d::dynamics::{GenericJoint, ImpulseJoint, JointAxis};
+use data_structure_utils::graphs::directed::EdgeID;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     effector::{CreatureJointEffector, CreatureJointEffectors},
+    limb::CreatureLimbBundle,
+    neural::NeuralBrain,
     CreatureId,
 };
 
+/// Identifies a joint within a creature by the morphology graph edge that
+/// created it and the order in which that edge was instanced while evaluating
+/// the graph, which stays the same across runs
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct CreatureJointId {
+    pub edge: EdgeID,
+    pub instance: usize,
+}
+
+/// The physical size of the limbs a joint connects, used to scale its force
+/// limit. Each value is the smaller of the two connected limbs
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CreatureJointStrength {
+    /// The largest cross-sectional area of the limb
+    pub cross_section: f32,
+    pub mass: f32,
+}
+
+impl Default for CreatureJointStrength {
+    fn default() -> Self {
+        // A 1x1x1 limb with a density of 1
+        Self { cross_section: 1.0, mass: 1.0 }
+    }
+}
+
+impl CreatureJointStrength {
+    pub fn between(parent: &CreatureLimbBundle, child: &CreatureLimbBundle) -> Self {
+        Self { cross_section: parent.cross_section().min(child.cross_section()), mass: parent.mass().min(child.mass()) }
+    }
+}
+
+
 #[derive(Component, Clone, Debug, Serialize, Deserialize)]
 pub struct CreatureJoint {
     pub creature: CreatureId,
+    pub id: CreatureJointId,
+    pub strength: CreatureJointStrength,
 }
 
 
@@ -24,7 +61,11 @@ pub struct CreatureJointBuilder {
 impl Default for CreatureJointBuilder {
     fn default() -> Self {
         Self {
-            joint: CreatureJoint { creature: CreatureId(0) },
+            joint: CreatureJoint {
+                creature: CreatureId(0),
+                id: CreatureJointId { edge: EdgeID(0), instance: 0 },
+                strength: CreatureJointStrength::default(),
+            },
             parent: Entity::PLACEHOLDER,
             data: GenericJoint::default(),
             effectors: CreatureJointEffectors::default(),
@@ -57,11 +98,30 @@ impl CreatureJointBuilder {
         self
     }
 
+    pub fn with_brain(mut self, brain: Option<NeuralBrain>) -> Self {
+        self.effectors.brain = brain;
+        self
+    }
+
     pub fn with_creature(mut self, id: CreatureId) -> Self {
         self.joint.creature = id;
         self
     }
 
+    pub fn with_id(mut self, id: CreatureJointId) -> Self {
+        self.joint.id = id;
+        self
+    }
+
+    pub fn with_strength(mut self, strength: CreatureJointStrength) -> Self {
+        self.joint.strength = strength;
+        self
+    }
+
+    pub fn id(&self) -> CreatureJointId {
+        self.joint.id
+    }
+
     pub fn finish(self) -> (ImpulseJoint, CreatureJointEffectors, CreatureJoint) {
         (ImpulseJoint::new(self.parent, self.data), self.effectors, self.joint)
     }
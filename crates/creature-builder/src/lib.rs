@@ -4,16 +4,17 @@ pub mod effector;
 pub mod expr;
 pub mod joint;
 pub mod limb;
+pub mod neural;
 pub mod sensor;
 
 
-use std::collections::{hash_map::Entry, HashMap};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 
 use bevy::prelude::*;
 use bevy_rapier3d::dynamics::{ExternalImpulse, ImpulseJoint, Velocity};
 use config::CreatureBuilderConfig;
-use effector::{CreatureContext, CreatureJointEffectors, JointContext};
-use joint::CreatureJoint;
+use effector::{CreatureCentralNodes, CreatureContext, CreatureEnergy, CreatureJointEffectors, JointContext};
+use joint::{CreatureJoint, CreatureJointStrength};
 use limb::CreatureLimb;
 use sensor::{update_sensor_status, LimbCollisionSensor};
 use serde::{Deserialize, Serialize};
@@ -23,24 +24,65 @@ use serde::{Deserialize, Serialize};
 pub struct CreatureId(pub usize);
 
 
+/// How the force limit of each joint is derived from `max_force`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ActuationStrength {
+    /// Every joint is limited to `max_force`
+    #[default]
+    Constant,
+    /// `max_force` is scaled by the cross-sectional area of the connected limbs
+    CrossSection,
+    /// `max_force` is scaled by the mass of the connected limbs
+    Mass,
+}
+
+impl ActuationStrength {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "constant" => Some(Self::Constant),
+            "cross-section" => Some(Self::CrossSection),
+            "mass" => Some(Self::Mass),
+            _ => None,
+        }
+    }
+
+    pub fn max_force(&self, max_force: f32, strength: &CreatureJointStrength) -> f32 {
+        match self {
+            Self::Constant => max_force,
+            Self::CrossSection => max_force * strength.cross_section,
+            Self::Mass => max_force * strength.mass,
+        }
+    }
+}
+
+
 pub struct CreatureBehaviorConfig {
+    /// The force limit of a joint between two 1x1x1 limbs
     pub max_force: f32,
+    /// How the force limit scales with the limbs a joint connects, `Constant`
+    /// if unset
+    pub actuation: Option<ActuationStrength>,
     pub max_rel_linvel: f32,
     pub max_angvel: f32,
     pub disable_behavior: bool,
+    /// The amount of work each creature can perform before its effectors are
+    /// disabled, if any
+    pub energy_budget: Option<f32>,
 }
 
 impl Default for CreatureBehaviorConfig {
     fn default() -> Self {
-        Self { max_force: 0.075, max_rel_linvel: 10.0, max_angvel: 10.0, disable_behavior: false }
+        Self { max_force: 0.075, actuation: None, max_rel_linvel: 10.0, max_angvel: 10.0, disable_behavior: false, energy_budget: None }
     }
 }
 
 
 fn behavior_main(
     time: Res<Time>,
-    mut joints: Query<(&CreatureJoint, &ImpulseJoint, &CreatureJointEffectors, Entity), With<CreatureJoint>>,
+    mut joints: Query<(&CreatureJoint, &ImpulseJoint, &mut CreatureJointEffectors, Entity), With<CreatureJoint>>,
     mut limbs: Query<(&LimbCollisionSensor, &Transform, &mut ExternalImpulse, &mut Velocity), With<CreatureLimb>>,
+    central_nodes: Query<(&CreatureLimb, &CreatureCentralNodes)>,
+    mut energies: Query<(&CreatureLimb, &mut CreatureEnergy)>,
     config: Res<CreatureBuilderConfig>,
 ) {
     if config.behavior.disable_behavior {
@@ -48,9 +90,8 @@ fn behavior_main(
     }
 
     let mut creature_contexts = HashMap::new();
-    let mut joint_indices = HashMap::new();
 
-    for (i, (joint_data, joint, _effectors, entity)) in joints.iter().enumerate() {
+    for (joint_data, joint, _effectors, entity) in joints.iter() {
         match creature_contexts.entry(joint_data.creature) {
             Entry::Vacant(entry) => {
                 let mut context = CreatureContext::new();
@@ -60,9 +101,8 @@ fn behavior_main(
                 let child_transform = *limbs.get(entity).unwrap().1;
                 let joint_context = JointContext::new(parent_contacts, child_contacts, &parent_transform, &child_transform);
                 context.set_time(time.elapsed_seconds());
-                context.add_joint(joint_context);
+                context.add_joint(joint_data.id, joint_context);
 
-                joint_indices.insert(i, 0);
                 entry.insert(context);
             },
             Entry::Occupied(mut entry) => {
@@ -72,25 +112,42 @@ fn behavior_main(
                 let child_transform = *limbs.get(entity).unwrap().1;
                 let context = JointContext::new(parent_contacts, child_contacts, &parent_transform, &child_transform);
 
-                joint_indices.insert(i, entry.get().len());
-                entry.get_mut().add_joint(context);
+                entry.get_mut().add_joint(joint_data.id, context);
             },
         }
     }
 
+    for (limb, central) in central_nodes.iter() {
+        let Some(context) = creature_contexts.get_mut(&limb.creature) else { continue };
+        central.evaluate(context);
+    }
+
     for (_, joint, _, entity) in joints.iter() {
         limbs.get_mut(joint.parent).unwrap().2.torque_impulse = Vec3::ZERO;
         limbs.get_mut(entity).unwrap().2.torque_impulse = Vec3::ZERO;
     }
 
-    for (i, (joint_data, joint, effectors, entity)) in joints.iter_mut().enumerate() {
-        creature_contexts.get_mut(&joint_data.creature).unwrap().set_current_joint(joint_indices[&i]);
+    let exhausted: HashSet<CreatureId> = match config.behavior.energy_budget {
+        Some(budget) => energies.iter().filter(|(_, energy)| energy.work >= budget).map(|(limb, _)| limb.creature).collect(),
+        None => HashSet::new(),
+    };
+    let mut step_energies: HashMap<CreatureId, CreatureEnergy> = HashMap::new();
+
+    for (joint_data, joint, mut effectors, entity) in joints.iter_mut() {
+        effectors.outputs = [0.0; 6];
+        if exhausted.contains(&joint_data.creature) {
+            continue;
+        }
+
+        creature_contexts.get_mut(&joint_data.creature).unwrap().set_current_joint(joint_data.id);
         let child_transform = *limbs.get(entity).unwrap().1;
         let context = creature_contexts.get(&joint_data.creature).unwrap();
 
-        for (i, effector) in effectors.effectors.iter().enumerate() {
-            let Some(effector) = effector else { continue };
-            let force = effector.expr.evaluate(context);
+        let max_force = config.behavior.actuation.unwrap_or_default().max_force(config.behavior.max_force, &joint_data.strength);
+
+        let forces = effectors.evaluate(context, joint.data.locked_axes());
+        for (i, force) in forces.into_iter().enumerate() {
+            let Some(force) = force else { continue };
 
             let (axis, rotational) = match i {
                 0 => (Vec3::X, false),
@@ -105,12 +162,24 @@ fn behavior_main(
             if rotational {
                 let rot_axis = child_transform.rotation * axis;
 
-                let torque = rot_axis * force.0.clamp(-config.behavior.max_force, config.behavior.max_force);
+                effectors.outputs[i] = force.clamp(-max_force, max_force);
+                let torque = rot_axis * effectors.outputs[i];
                 limbs.get_mut(joint.parent).unwrap().2.torque_impulse += -torque;
                 limbs.get_mut(entity).unwrap().2.torque_impulse += torque;
+
+                let rel_angvel = limbs.get(entity).unwrap().3.angvel - limbs.get(joint.parent).unwrap().3.angvel;
+                let energy = step_energies.entry(joint_data.creature).or_default();
+                energy.work += torque.dot(rel_angvel).abs();
+                energy.effort += torque.length();
             }
         }
     }
+
+    for (limb, mut energy) in energies.iter_mut() {
+        let Some(step_energy) = step_energies.get(&limb.creature) else { continue };
+        energy.work += step_energy.work;
+        energy.effort += step_energy.effort;
+    }
 }
 
 
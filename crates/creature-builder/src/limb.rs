@@ -10,6 +10,17 @@ use crate::{
 };
 
 
+/// The mass of a limb with the given scale, treating it as a solid box
+pub fn box_mass(scale: Vec3, mass: &ColliderMassProperties) -> f32 {
+    let size = scale * 2.0;
+    match mass {
+        ColliderMassProperties::Density(density) => density * size.x * size.y * size.z,
+        ColliderMassProperties::Mass(mass) => *mass,
+        ColliderMassProperties::MassProperties(properties) => properties.mass,
+    }
+}
+
+
 #[derive(Component, Clone, Debug, Serialize, Deserialize)]
 pub struct CreatureLimb {
     pub creature: CreatureId,
@@ -155,6 +166,16 @@ impl CreatureLimbBundle {
         self
     }
 
+    /// The area of the largest face of the limb
+    pub fn cross_section(&self) -> f32 {
+        let size = self.transform.scale * 2.0;
+        (size.x * size.y).max(size.y * size.z).max(size.x * size.z)
+    }
+
+    pub fn mass(&self) -> f32 {
+        box_mass(self.transform.scale, &self.mass)
+    }
+
     pub fn finish(mut self, meshes: &mut ResMut<Assets<Mesh>>, materials: &mut ResMut<Assets<StandardMaterial>>) -> Self {
         self.mesh = meshes.add(Mesh::from(shape::Box::new(2.0, 2.0, 2.0)));
         self.material = materials.add(StandardMaterial::from(self.color));
@@ -0,0 +1,109 @@
+use serde::{Deserialize, Serialize};
+
+use crate::effector::{CreatureContext, CreatureContextElement, JointContextElement};
+
+
+/// A small recurrent neural network that drives the joints of a creature.
+///
+/// Every joint of the creature runs its own copy of the network with its own
+/// hidden state. The input neurons read every `JointContextElement` sensor of
+/// that joint and the output neurons produce one force per joint axis.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NeuralBrain {
+    /// The weights into each hidden neuron.
+    /// Ordered: [inputs.., hidden..]
+    pub hidden_weights: Vec<Vec<f32>>,
+    pub hidden_biases: Vec<f32>,
+    /// The weights from each hidden neuron into each output neuron.
+    /// Ordered: [X, Y, Z, AngX, AngY, AngZ]
+    pub output_weights: [Vec<f32>; 6],
+    pub output_biases: [f32; 6],
+    #[serde(skip)]
+    activations: Vec<f32>,
+}
+
+impl NeuralBrain {
+    /// The number of input neurons, one for every `JointContextElement`
+    pub const INPUTS: usize = JointContextElement::COUNT;
+
+    /// Creates a brain with `hidden` neurons and all weights set to zero
+    pub fn new(hidden: usize) -> Self {
+        Self {
+            hidden_weights: vec![vec![0.0; Self::INPUTS + hidden]; hidden],
+            hidden_biases: vec![0.0; hidden],
+            output_weights: std::array::from_fn(|_| vec![0.0; hidden]),
+            output_biases: [0.0; 6],
+            activations: vec![0.0; hidden],
+        }
+    }
+
+    pub fn hidden_len(&self) -> usize {
+        self.hidden_biases.len()
+    }
+
+    /// Adds a hidden neuron with no incoming or outgoing connections
+    pub fn add_hidden(&mut self) {
+        for weights in self.hidden_weights.iter_mut() {
+            weights.push(0.0);
+        }
+        self.hidden_weights.push(vec![0.0; Self::INPUTS + self.hidden_len() + 1]);
+        self.hidden_biases.push(0.0);
+        for weights in self.output_weights.iter_mut() {
+            weights.push(0.0);
+        }
+        self.activations.clear();
+    }
+
+    /// Removes a hidden neuron along with all of its connections
+    pub fn remove_hidden(&mut self, index: usize) {
+        self.hidden_weights.remove(index);
+        for weights in self.hidden_weights.iter_mut() {
+            weights.remove(Self::INPUTS + index);
+        }
+        self.hidden_biases.remove(index);
+        for weights in self.output_weights.iter_mut() {
+            weights.remove(index);
+        }
+        self.activations.clear();
+    }
+
+    /// Steps the network once using the sensors of the current joint in
+    /// `context`, returning the output of each axis.
+    ///
+    /// Ordered: [X, Y, Z, AngX, AngY, AngZ]
+    pub fn evaluate(&mut self, context: &CreatureContext) -> [f32; 6] {
+        let hidden = self.hidden_len();
+        if self.activations.len() != hidden {
+            self.activations = vec![0.0; hidden];
+        }
+
+        let inputs: Vec<f32> = (0..Self::INPUTS)
+            .map(|i| {
+                let element = JointContextElement::from_index(i);
+                context.index(CreatureContextElement::LocalJoint { element }).filter(|v| v.is_finite()).unwrap_or(0.0)
+            })
+            .collect();
+
+        let activations: Vec<f32> = self
+            .hidden_weights
+            .iter()
+            .zip(self.hidden_biases.iter())
+            .map(|(weights, bias)| {
+                let sum = inputs.iter().chain(self.activations.iter()).zip(weights.iter()).fold(*bias, |acc, (x, w)| acc + x * w);
+                if sum.is_finite() {
+                    sum.tanh()
+                } else {
+                    0.0
+                }
+            })
+            .collect();
+        self.activations = activations;
+
+        let mut outputs = [0.0; 6];
+        for (output, (weights, bias)) in outputs.iter_mut().zip(self.output_weights.iter().zip(self.output_biases.iter())) {
+            let sum = self.activations.iter().zip(weights.iter()).fold(*bias, |acc, (x, w)| acc + x * w);
+            *output = if sum.is_finite() { sum } else { 0.0 };
+        }
+        outputs
+    }
+}
@@ -10,6 +10,7 @@ use serde::{Deserialize, Serialize};
 use crate::{
     builder::placement::LimbAttachFace,
     config::{ActiveCollisionTypes, CreatureBuilderConfig},
+    limb::CreatureLimb,
 };
 
 
@@ -17,6 +18,8 @@ use crate::{
 pub enum ContactFilterTag {
     GroundGroup,
     LimbGroup,
+    /// Free bodies in the environment, which collide with everything
+    ObjectGroup,
 }
 
 
@@ -26,6 +29,18 @@ pub struct LimbCollisionSensor {
     pub(crate) entities: HashMap<Entity, LimbAttachFace>,
 }
 
+impl LimbCollisionSensor {
+    /// What each face of the limb is touching, ordered like `LimbAttachFace`
+    pub fn faces(&self) -> [LimbCollisionType; 6] {
+        self.faces
+    }
+
+    /// Whether any face of the limb is touching the ground
+    pub fn touching_ground(&self) -> bool {
+        self.faces.contains(&LimbCollisionType::GroundCollision)
+    }
+}
+
 impl Index<LimbAttachFace> for LimbCollisionSensor {
     type Output = LimbCollisionType;
 
@@ -45,6 +60,9 @@ impl IndexMut<LimbAttachFace> for LimbCollisionSensor {
 pub enum LimbCollisionType {
     SelfCollision,
     GroundCollision,
+    /// Touching a limb of another creature
+    CreatureCollision,
+    ObjectCollision,
     None,
 }
 
@@ -52,6 +70,7 @@ pub enum LimbCollisionType {
 #[derive(SystemParam)]
 pub struct ContactFilter<'w, 's> {
     pub(crate) tags: Query<'w, 's, &'static ContactFilterTag>,
+    pub(crate) limbs: Query<'w, 's, &'static CreatureLimb>,
     pub(crate) config: Res<'w, CreatureBuilderConfig>,
 }
 
@@ -60,10 +79,19 @@ impl BevyPhysicsHooks for ContactFilter<'_, '_> {
         let tag1 = self.tags.get(context.collider1()).ok().copied()?;
         let tag2 = self.tags.get(context.collider2()).ok().copied()?;
 
+        if tag1 == ContactFilterTag::ObjectGroup || tag2 == ContactFilterTag::ObjectGroup {
+            return Some(SolverFlags::COMPUTE_IMPULSES);
+        }
+
         let mut limb_ground_collision = false;
         let mut limb_limb_collision = false;
+        let mut limb_creature_collision = false;
         if tag1 == ContactFilterTag::LimbGroup && tag2 == ContactFilterTag::LimbGroup {
-            limb_limb_collision = true;
+            if same_creature(&self.limbs, context.collider1(), context.collider2()) {
+                limb_limb_collision = true;
+            } else {
+                limb_creature_collision = true;
+            }
         }
 
         if tag1 == ContactFilterTag::LimbGroup && tag2 == ContactFilterTag::GroundGroup {
@@ -76,6 +104,7 @@ impl BevyPhysicsHooks for ContactFilter<'_, '_> {
 
         if (limb_ground_collision && self.config.collision_types.contains(ActiveCollisionTypes::LIMB_VS_GROUND))
             || (limb_limb_collision && self.config.collision_types.contains(ActiveCollisionTypes::LIMB_VS_LIMB))
+            || (limb_creature_collision && self.config.collision_types.contains(ActiveCollisionTypes::LIMB_VS_CREATURE))
         {
             Some(SolverFlags::COMPUTE_IMPULSES)
         } else {
@@ -85,9 +114,30 @@ impl BevyPhysicsHooks for ContactFilter<'_, '_> {
 }
 
 
+/// Whether two limbs belong to the same creature
+fn same_creature(limbs: &Query<&CreatureLimb>, limb_1: Entity, limb_2: Entity) -> bool {
+    match (limbs.get(limb_1), limbs.get(limb_2)) {
+        (Ok(limb_1), Ok(limb_2)) => limb_1.creature == limb_2.creature,
+        _ => true,
+    }
+}
+
+
+/// The kind of collision a limb has with a body of the given tag
+fn limb_collision_type(limbs: &Query<&CreatureLimb>, limb: Entity, other: Entity, tag: ContactFilterTag) -> LimbCollisionType {
+    match tag {
+        ContactFilterTag::GroundGroup => LimbCollisionType::GroundCollision,
+        ContactFilterTag::ObjectGroup => LimbCollisionType::ObjectCollision,
+        ContactFilterTag::LimbGroup if same_creature(limbs, limb, other) => LimbCollisionType::SelfCollision,
+        ContactFilterTag::LimbGroup => LimbCollisionType::CreatureCollision,
+    }
+}
+
+
 pub(crate) fn update_sensor_status(
     mut collision_events: EventReader<CollisionEvent>,
     tags: Query<&ContactFilterTag>,
+    limbs: Query<&CreatureLimb>,
     mut sensors: Query<&mut LimbCollisionSensor>,
     context: Res<RapierContext>,
 ) {
@@ -102,23 +152,17 @@ pub(crate) fn update_sensor_status(
             let Ok(tag_1) = tags.get(*entity_1) else { continue };
             let Ok(tag_2) = tags.get(*entity_2) else { continue };
 
-            if *tag_1 == ContactFilterTag::LimbGroup && *tag_2 == ContactFilterTag::GroundGroup {
-                let Ok(mut sensor) = sensors.get_mut(*entity_1) else { continue };
-                sensor[face_1] = LimbCollisionType::GroundCollision;
-                sensor.entities.insert(*entity_2, face_1);
-            }
-            if *tag_2 == ContactFilterTag::LimbGroup && *tag_1 == ContactFilterTag::GroundGroup {
-                let Ok(mut sensor) = sensors.get_mut(*entity_2) else { continue };
-                sensor[face_2] = LimbCollisionType::GroundCollision;
-                sensor.entities.insert(*entity_1, face_1);
+            if *tag_1 == ContactFilterTag::LimbGroup {
+                if let Ok(mut sensor) = sensors.get_mut(*entity_1) {
+                    sensor[face_1] = limb_collision_type(&limbs, *entity_1, *entity_2, *tag_2);
+                    sensor.entities.insert(*entity_2, face_1);
+                }
             }
-            if *tag_1 == ContactFilterTag::LimbGroup && *tag_2 == ContactFilterTag::LimbGroup {
-                let Ok(mut sensor_1) = sensors.get_mut(*entity_1) else { continue };
-                sensor_1[face_1] = LimbCollisionType::SelfCollision;
-                sensor_1.entities.insert(*entity_2, face_1);
-                let Ok(mut sensor_2) = sensors.get_mut(*entity_2) else { continue };
-                sensor_2[face_2] = LimbCollisionType::SelfCollision;
-                sensor_2.entities.insert(*entity_1, face_2);
+            if *tag_2 == ContactFilterTag::LimbGroup {
+                if let Ok(mut sensor) = sensors.get_mut(*entity_2) {
+                    sensor[face_2] = limb_collision_type(&limbs, *entity_2, *entity_1, *tag_1);
+                    sensor.entities.insert(*entity_1, face_2);
+                }
             }
         } else if let CollisionEvent::Stopped(entity_1, entity_2, _flags) = collision_event {
             if let Ok(mut sensor_1) = sensors.get_mut(*entity_1) {
@@ -0,0 +1,25 @@
+use bevy::math::Vec3;
+use creature_builder::{joint::CreatureJointStrength, limb::CreatureLimbBundle, ActuationStrength};
+
+
+#[test]
+fn max_force() {
+    let strength = CreatureJointStrength { cross_section: 0.5, mass: 4.0 };
+    assert_eq!(ActuationStrength::Constant.max_force(0.1, &strength), 0.1);
+    assert_eq!(ActuationStrength::CrossSection.max_force(0.1, &strength), 0.05);
+    assert_eq!(ActuationStrength::Mass.max_force(0.1, &strength), 0.4);
+
+    assert_eq!(ActuationStrength::from_name("cross-section"), Some(ActuationStrength::CrossSection));
+    assert_eq!(ActuationStrength::from_name("area"), None);
+}
+
+
+#[test]
+fn between() {
+    // A 2x1x1 limb with a density of 3 and a 1x1x0.5 limb with a density of 1
+    let large = CreatureLimbBundle::new().with_size(Vec3::new(1.0, 0.5, 0.5)).with_density(3.0);
+    let small = CreatureLimbBundle::new().with_size(Vec3::new(0.5, 0.5, 0.25)).with_density(1.0);
+    assert_eq!(CreatureJointStrength::between(&large, &small), CreatureJointStrength { cross_section: 1.0, mass: 0.5 });
+    assert_eq!(CreatureJointStrength::between(&small, &large), CreatureJointStrength::between(&large, &small));
+    assert_eq!(CreatureJointStrength::between(&large, &large), CreatureJointStrength { cross_section: 2.0, mass: 6.0 });
+}
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use creature_builder::{
+    builder::{
+        node::{CreatureMorphologyGraph, LimbConnection, LimbNode},
+        placement::{LimbAttachFace, LimbRelativePlacement},
+    },
+    config::CreatureBuilderConfig,
+    effector::{CreatureEnergy, CreatureJointEffector, CreatureJointEffectors},
+    expr::{node::ExprNode, value::ExprValue, Expr},
+    sensor::ContactFilter,
+    CreatureBuilderPlugin, CreatureId,
+};
+
+
+/// A body with one leg that is always twisted as hard as the joint allows
+fn spawn_creature(mut commands: Commands) {
+    let mut graph = CreatureMorphologyGraph::new(CreatureId(0));
+    let limb = LimbNode { name: None, density: 1.0, friction: 0.3, restitution: 0.0, terminal_only: false, recursive_limit: 1 };
+    let body = graph.add_node(limb.clone());
+    let leg = graph.add_node(limb);
+
+    let twist = CreatureJointEffector { expr: Expr { root: ExprNode::Constant(ExprValue(1.0)) } };
+    graph.add_edge(
+        LimbConnection {
+            placement: LimbRelativePlacement {
+                attach_face: LimbAttachFace::PosX,
+                attach_position: Vec2::ZERO,
+                orientation: Quat::IDENTITY,
+                scale: Vec3::splat(0.8),
+                max_scale: Vec3::splat(1.0),
+                min_scale: Vec3::splat(0.1),
+            },
+            locked_axes: JointAxesMask::LIN_AXES,
+            limit_axes: [[1.0; 2]; 6],
+            effectors: CreatureJointEffectors::new([None, None, None, Some(twist), None, None]),
+        },
+        body,
+        leg,
+    );
+    graph.set_root(body);
+    graph.evaluate().build_nowindow(&mut commands);
+}
+
+
+fn app(energy_budget: Option<f32>) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(TransformPlugin)
+        .add_plugins(HierarchyPlugin)
+        .add_plugins(CreatureBuilderPlugin)
+        .add_plugins(RapierPhysicsPlugin::<ContactFilter>::default())
+        .insert_resource(RapierConfiguration {
+            gravity: Vec3::ZERO,
+            timestep_mode: TimestepMode::Fixed { dt: 1.0 / 60.0, substeps: 1 },
+            ..default()
+        })
+        .add_systems(Startup, spawn_creature);
+    app.world.resource_mut::<CreatureBuilderConfig>().behavior.energy_budget = energy_budget;
+    app
+}
+
+
+fn energy(app: &mut App) -> CreatureEnergy {
+    *app.world.query::<&CreatureEnergy>().single(&app.world)
+}
+
+
+fn outputs(app: &mut App) -> Vec<[f32; 6]> {
+    app.world.query::<&CreatureJointEffectors>().iter(&app.world).map(|effectors| effectors.outputs).collect()
+}
+
+
+#[test]
+fn accumulation() {
+    let mut app = app(None);
+    (0..30).for_each(|_| app.update());
+    let first = energy(&mut app);
+    assert!(first.work > 0.0 && first.effort > 0.0);
+
+    (0..30).for_each(|_| app.update());
+    let second = energy(&mut app);
+    assert!(second.work > first.work && second.effort > first.effort);
+    assert!(outputs(&mut app).iter().any(|outputs| outputs[3] != 0.0));
+}
+
+
+#[test]
+fn budget() {
+    let budget = 1e-4;
+    let mut app = app(Some(budget));
+    for _ in 0..300 {
+        app.update();
+        if energy(&mut app).work >= budget {
+            break;
+        }
+    }
+    let spent = energy(&mut app);
+    assert!(spent.work >= budget);
+
+    // The effectors stop once the budget is spent
+    (0..10).for_each(|_| app.update());
+    assert_eq!(energy(&mut app).work, spent.work);
+    assert!(outputs(&mut app).iter().all(|outputs| *outputs == [0.0; 6]));
+}
@@ -9,6 +9,7 @@ pub struct EdgeID(pub usize);
 
 
 pub trait NodeData<E: EdgeData, R: DirectedGraphResult, P: DirectedGraphParameters> {
+    #[allow(clippy::too_many_arguments)]
     fn evaluate(
         &self,
         result: &mut R,
@@ -36,6 +37,7 @@ impl<N: NodeData<E, R, P>, E: EdgeData, R: DirectedGraphResult, P: DirectedGraph
         Self { outs: Vec::new(), data, phantom: PhantomData, phantom2: PhantomData, phantom3: PhantomData }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn evaluate(
         &self,
         result: &mut R,
@@ -1,6 +1,17 @@
 use std::{env, fs, process::Command, time::Duration};
 
-use behavior_evolver::evolution::write;
+use behavior_evolver::evolution::{
+    curriculum::Curriculum,
+    diagnostics::{ExploitLimits, ExploitPenalty},
+    fitness::composite::{CompositeFitnessEval, FitnessTerm},
+    island::IslandSettings,
+    map_elites::CreatureTrait,
+    terrain::Terrain,
+    trajectory::Trajectory,
+    trials::{TrialAggregation, TrialPerturbation},
+    write, EnvironmentConfig,
+};
+use creature_builder::ActuationStrength;
 use playback::{PlaybackConfig, PlaybackMode};
 use train::TrainConfig;
 
@@ -34,7 +45,7 @@ fn print_help(args: &[String]) {
     println!("    {} train [session] [TRAIN OPTIONS]", args[0]);
     println!("            Begin a new or attach to an existing training session");
     println!();
-    println!("    {} play [session] [-c|-g] [PLAYBACK OPTIONS]", args[0]);
+    println!("    {} play [session] [-c|-g|-b|-l|-p|-i|-x] [PLAYBACK OPTIONS]", args[0]);
     println!("            Playback a creature or entire generation");
     println!();
     println!("    {} plist [playlist|-l] [PLAYLIST OPTIONS]", args[0]);
@@ -43,6 +54,9 @@ fn print_help(args: &[String]) {
     println!("    {} session [name|-l] [SESSION OPTIONS]", args[0]);
     println!("            Perform operations on training sessions");
     println!();
+    println!("    {} trajectory [session] [TRAJECTORY OPTIONS]", args[0]);
+    println!("            Summarize or export the trajectories recorded while training");
+    println!();
     println!("    {} help", args[0]);
     println!("            Display this message");
     println!();
@@ -84,9 +98,144 @@ fn print_help(args: &[String]) {
     println!();
     println!("    -f, --fitness <FITNESS_FN>");
     println!("            The fitness function to use when evaluating creatures");
-    println!("            Options: [jump, walk]");
+    println!("            Options: [jump, walk, straight, climb, push, swim, compete, composite[:TERM=WEIGHT,...]]");
+    println!("            straight rewards walking along the heading without drifting or turning");
+    println!("            climb rewards height held until the end of the test, best with -g stairs");
+    println!("            push rewards pushing a block along the heading, spawned 2 ahead towards a goal 10 ahead");
+    println!("            swim trains in water instead of on the ground");
+    println!("            compete matches pairs of creatures over a cube, scoring control of it");
+    println!("            composite sums the weighted terms, or scores displacement if none are given");
+    println!("            Terms: [height, displacement, forward, speed, upright, energy, contact]");
+    println!("            Resumed sessions keep the weights they were trained with unless new ones are given");
     println!("            Default: jump");
     println!();
+    println!("    -c, --controller <CONTROLLER>");
+    println!("            The representation used to control the joints of new creatures");
+    println!("            Options: [expr, neural]");
+    println!("            Default: expr");
+    println!();
+    println!("    -g, --terrain <TERRAIN>");
+    println!("            The ground creatures are tested on, ignored when swimming");
+    println!("            Options: [flat, hills, stairs, slope, boxes, gaps]");
+    println!("            Default: flat");
+    println!();
+    println!("    --world <ENVIRONMENT_FILE>");
+    println!("            A RON file describing the gravity, timestep, ground, terrain and fluid");
+    println!("            of the environment, replacing the one chosen by --fitness and --terrain");
+    println!("            Default: unset");
+    println!();
+    println!("    -u, --curriculum <CURRICULUM_FILE>");
+    println!("            A RON file listing stages that change the environment, test time and");
+    println!("            fitness weights after a given generation or best fitness");
+    println!("            Only walk, straight, push, compete and composite have fitness weights");
+    println!("            Default: unset");
+    println!();
+    println!("    -m, --selection <SELECTION>");
+    println!("            How creatures are retained into the next generation");
+    println!("            nsga2 keeps the best Pareto fronts of the fitness objectives, saving the");
+    println!("            first front of each generation in pareto-fronts/ in the session");
+    println!("            Options: [elitism, nsga2]");
+    println!("            Default: elitism");
+    println!();
+    println!("    -y, --novelty <NOVELTY_WEIGHT>");
+    println!("            Reward creatures for novel behavior, blending the novelty score into");
+    println!("            selection by a weight from 0 (pure fitness) to 1 (pure novelty)");
+    println!("            With nsga2 selection novelty is added as an extra objective");
+    println!("            Default: unset; no novelty search");
+    println!();
+    println!("    -x, --map-elites <TRAITS>");
+    println!("            Keep the fittest creature of each cell of a grid over a comma separated");
+    println!("            list of traits, breeding every new creature from the grid");
+    println!("            Options: [limbs, height, speed, contact]");
+    println!("            Default: unset; generational selection");
+    println!();
+    println!("    -k, --speciate <THRESHOLD>");
+    println!("            Group creatures into species by genotype distance, sharing fitness");
+    println!("            within each species and giving each species its own offspring");
+    println!("            Can't be used with --map-elites");
+    println!("            Default: unset; no speciation");
+    println!();
+    println!("    -i, --islands <ISLANDS>");
+    println!("            The number of islands, each evolving its own population of");
+    println!("            <POPULATION> creatures and sending its best creatures to the next");
+    println!("            Default: 1");
+    println!();
+    println!("    -j, --migration <INTERVAL>");
+    println!("            The number of generations between migrations between islands");
+    println!("            Default: 10");
+    println!();
+    println!("    --migrants <COUNT>");
+    println!("            The number of retained creatures each island sends to the next");
+    println!("            Default: 2");
+    println!();
+    println!("    --island-settings <ISLAND_FILE>");
+    println!("            A RON list giving each island, in order, its own elitism, rand_percent,");
+    println!("            num_mutations or selection, replacing the values shared by every island");
+    println!("            Default: unset; every island uses the shared values");
+    println!();
+    println!("    -q, --opponents <COUNT>");
+    println!("            The number of opponents each creature plays when competing");
+    println!("            Default: 4");
+    println!();
+    println!("    -a, --champions");
+    println!("            Compete against the champions of the latest <COUNT> generations");
+    println!("            instead of creatures of the same generation");
+    println!();
+    println!("    -l, --hall-of-fame <SIZE>");
+    println!("            The number of the fittest creatures of the session to keep");
+    println!("            Default: 10");
+    println!();
+    println!("    -z, --cache <INTERVAL>");
+    println!("            Reuse the fitness of creatures that were already tested, such as");
+    println!("            retained creatures, testing them again every <INTERVAL> generations");
+    println!("            and averaging the results. 0 never tests them again");
+    println!("            Default: unset; every creature is tested every generation");
+    println!();
+    println!("    --trials <TRIALS>");
+    println!("            The number of times each creature is tested, combining the scores of");
+    println!("            every trial into its fitness");
+    println!("            Default: 1");
+    println!();
+    println!("    --aggregate <RULE>");
+    println!("            How the scores of the trials are combined, where std:<K> is the mean");
+    println!("            less <K> standard deviations");
+    println!("            Options: [mean, min, median, std:<K>]");
+    println!("            Default: mean");
+    println!();
+    println!("    --perturb <YAW,OFFSET[,reseed]>");
+    println!("            Turn each trial's creature by up to <YAW> radians either way and move");
+    println!("            it up to <OFFSET> along the ground, generating random terrain from a");
+    println!("            new seed in each trial if reseed is given");
+    println!("            Default: unset; every trial is spawned the same");
+    println!();
+    println!("    -E, --exploits <PENALTY[,SEPARATION,PENETRATION,ENERGY_GAIN]>");
+    println!("            Penalize creatures whose joints come apart by more than <SEPARATION>,");
+    println!("            that sink deeper than <PENETRATION> into the ground or that gain more");
+    println!("            than <ENERGY_GAIN> kinetic energy per unit of mass in one step, by");
+    println!("            taking <PENALTY> from their fitness for each limit broken");
+    println!("            disqualify gives them the lowest fitness instead");
+    println!("            Not available with the compete fitness");
+    println!("            Default: unset; limits of 0.25,0.1,20 when only <PENALTY> is given");
+    println!();
+    println!("    --energy-budget <WORK>");
+    println!("            The work each creature can do in a test before its joints stop moving");
+    println!("            Default: unset; no limit, or the budget the session was trained with");
+    println!();
+    println!("    --actuation <STRENGTH>");
+    println!("            How the force limit of each joint scales with the limbs it connects");
+    println!("            Options: [constant, cross-section, mass]");
+    println!("            Default: unset; constant, or the scaling the session was trained with");
+    println!();
+    println!("    --heading <DEGREES>");
+    println!("            The direction along the ground the straight, push and composite fitnesses reward");
+    println!("            moving in, measured from +x towards +z");
+    println!("            Default: unset; 0, or the heading the session was trained with");
+    println!();
+    println!("    -T, --trajectories <INTERVAL>");
+    println!("            Record the state of every tested creature every <INTERVAL> steps, saved");
+    println!("            for each generation in trajectories/ in the session");
+    println!("            Default: unset; no recording");
+    println!();
     println!("PLAYBACK OPTIONS:");
     println!("    -c, --creature <CREATURE_ID>");
     println!("            Playback a specific creature");
@@ -97,6 +246,21 @@ fn print_help(args: &[String]) {
     println!("    -b, --best");
     println!("            Playback the best creature");
     println!();
+    println!("    -l, --hall-of-fame");
+    println!("            Playback the fittest creatures of the whole session");
+    println!();
+    println!("    -p, --pareto");
+    println!("            Playback the Pareto front of the last generation of a session trained");
+    println!("            with nsga2 selection");
+    println!();
+    println!("    -i, --island <ISLAND>");
+    println!("            Playback the creatures of one island of the last generation");
+    println!();
+    println!("    -x, --elites <CELL>");
+    println!("            Playback the MAP-Elites grid, either every cell or a single cell given");
+    println!("            as comma separated indices, e.g. 3,1");
+    println!("            Options: [all, <CELL>]");
+    println!();
     println!("    -a, --auto-cycle <CYCLE_DELAY>");
     println!("            Enable auto-cycling through creatures with specified delay");
     println!("            Default: unset; no auto-cycle");
@@ -117,6 +281,18 @@ fn print_help(args: &[String]) {
     println!();
     println!("    -l, --list");
     println!("            List all sessions");
+    println!();
+    println!("TRAJECTORY OPTIONS:");
+    println!("    -g, --generation <GENERATION_ID>");
+    println!("            Read the trajectories of a specific generation");
+    println!("            Default: the latest recorded generation");
+    println!();
+    println!("    -c, --creature <CREATURE_ID>");
+    println!("            Only read the trajectories of one creature");
+    println!();
+    println!("    -e, --export <FILE>");
+    println!("            Write the trajectories to <FILE> as CSV with one row per step, for plotting,");
+    println!("            instead of printing a summary of each");
 }
 
 fn parse_args(args: Vec<String>) -> Result<(), InvalidUsageError> {
@@ -149,15 +325,141 @@ fn parse_args(args: Vec<String>) -> Result<(), InvalidUsageError> {
                         expect_res(expect(opts.next(), "Expected <RAND_PERCENT>")?.parse::<f32>(), "Invalid <RAND_PERCENT>")?;
                 } else if arg == "-f" || arg == "--fitness" {
                     let fun = expect(opts.next(), "Expected <FITNESS_FN>")?;
-                    if fun == "jump" || fun == "walk" {
+                    if matches!(fun.as_str(), "jump" | "walk" | "straight" | "climb" | "push" | "swim" | "compete" | "composite") {
                         train_config.fitness_fn = fun.to_string();
+                    } else if let Some(terms) = fun.strip_prefix("composite:") {
+                        let mut weights = Vec::new();
+                        for term in terms.split(',') {
+                            let (name, weight) = expect(term.split_once('='), "Expected <TERM=WEIGHT>")?;
+                            let term = expect(FitnessTerm::from_name(name), "Invalid <TERM>")?;
+                            weights.push((term, expect_res(weight.parse::<f32>(), "Invalid <WEIGHT>")?));
+                        }
+                        train_config.fitness_fn = String::from("composite");
+                        train_config.fitness_weights = Some(CompositeFitnessEval::blend(&weights));
                     } else {
                         return err("Invalid <FITNESS_FN>");
                     }
+                } else if arg == "-c" || arg == "--controller" {
+                    let controller = expect(opts.next(), "Expected <CONTROLLER>")?;
+                    if controller == "expr" || controller == "neural" {
+                        train_config.controller = controller.to_string();
+                    } else {
+                        return err("Invalid <CONTROLLER>");
+                    }
+                } else if arg == "-g" || arg == "--terrain" {
+                    let terrain = expect(opts.next(), "Expected <TERRAIN>")?;
+                    if Terrain::from_name(terrain).is_some() {
+                        train_config.terrain = terrain.to_string();
+                    } else {
+                        return err("Invalid <TERRAIN>");
+                    }
+                } else if arg == "--world" {
+                    let path = expect(opts.next(), "Expected <ENVIRONMENT_FILE>")?;
+                    train_config.environment = Some(expect_res(EnvironmentConfig::load(path), "Invalid <ENVIRONMENT_FILE>")?);
+                } else if arg == "-u" || arg == "--curriculum" {
+                    let path = expect(opts.next(), "Expected <CURRICULUM_FILE>")?;
+                    train_config.curriculum = Some(expect_res(Curriculum::load(path), "Invalid <CURRICULUM_FILE>")?);
+                } else if arg == "-m" || arg == "--selection" {
+                    let selection = expect(opts.next(), "Expected <SELECTION>")?;
+                    if selection == "elitism" || selection == "nsga2" {
+                        train_config.selection = selection.to_string();
+                    } else {
+                        return err("Invalid <SELECTION>");
+                    }
+                } else if arg == "-y" || arg == "--novelty" {
+                    train_config.novelty =
+                        Some(expect_res(expect(opts.next(), "Expected <NOVELTY_WEIGHT>")?.parse::<f32>(), "Invalid <NOVELTY_WEIGHT>")?);
+                } else if arg == "-x" || arg == "--map-elites" {
+                    let names = expect(opts.next(), "Expected <TRAITS>")?;
+                    let traits: Option<Vec<_>> = names.split(',').map(CreatureTrait::from_name).collect();
+                    train_config.map_elites = Some(expect(traits, "Invalid <TRAITS>")?);
+                } else if arg == "-k" || arg == "--speciate" {
+                    train_config.speciation =
+                        Some(expect_res(expect(opts.next(), "Expected <THRESHOLD>")?.parse::<f32>(), "Invalid <THRESHOLD>")?);
+                } else if arg == "-i" || arg == "--islands" {
+                    train_config.islands =
+                        expect_res(expect(opts.next(), "Expected <ISLANDS>")?.parse::<usize>(), "Invalid <ISLANDS>")?.max(1);
+                } else if arg == "-q" || arg == "--opponents" {
+                    train_config.opponents = expect_res(expect(opts.next(), "Expected <COUNT>")?.parse::<usize>(), "Invalid <COUNT>")?;
+                } else if arg == "-a" || arg == "--champions" {
+                    train_config.champions = true;
+                } else if arg == "-l" || arg == "--hall-of-fame" {
+                    train_config.hall_of_fame = expect_res(expect(opts.next(), "Expected <SIZE>")?.parse::<usize>(), "Invalid <SIZE>")?;
+                } else if arg == "-z" || arg == "--cache" {
+                    train_config.cache =
+                        Some(expect_res(expect(opts.next(), "Expected <INTERVAL>")?.parse::<usize>(), "Invalid <INTERVAL>")?);
+                } else if arg == "--trials" {
+                    train_config.trials =
+                        expect_res(expect(opts.next(), "Expected <TRIALS>")?.parse::<usize>(), "Invalid <TRIALS>")?.max(1);
+                } else if arg == "--aggregate" {
+                    let rule = expect(opts.next(), "Expected <RULE>")?;
+                    train_config.aggregation = expect(TrialAggregation::from_name(rule), "Invalid <RULE>")?;
+                } else if arg == "--perturb" {
+                    let mut values = expect(opts.next(), "Expected <YAW,OFFSET[,reseed]>")?.split(',');
+                    let yaw = expect_res(expect(values.next(), "Expected <YAW>")?.parse::<f32>(), "Invalid <YAW>")?;
+                    let offset = expect_res(expect(values.next(), "Expected <OFFSET>")?.parse::<f32>(), "Invalid <OFFSET>")?;
+                    train_config.perturbation = TrialPerturbation::new(yaw, offset);
+                    match values.next() {
+                        Some("reseed") => train_config.perturbation = train_config.perturbation.with_terrain_reseeding(),
+                        Some(_) => return err("Invalid <YAW,OFFSET[,reseed]>"),
+                        None => (),
+                    }
+                } else if arg == "--heading" {
+                    let degrees = expect_res(expect(opts.next(), "Expected <DEGREES>")?.parse::<f32>(), "Invalid <DEGREES>")?;
+                    train_config.heading = Some(degrees);
+                } else if arg == "-T" || arg == "--trajectories" {
+                    train_config.trajectories =
+                        Some(expect_res(expect(opts.next(), "Expected <INTERVAL>")?.parse::<usize>(), "Invalid <INTERVAL>")?);
+                } else if arg == "-E" || arg == "--exploits" {
+                    let mut values = expect(opts.next(), "Expected <PENALTY[,SEPARATION,PENETRATION,ENERGY_GAIN]>")?.split(',');
+                    let penalty = match expect(values.next(), "Expected <PENALTY>")? {
+                        "disqualify" => ExploitPenalty::Disqualify,
+                        amount => ExploitPenalty::Penalize(expect_res(amount.parse::<f32>(), "Invalid <PENALTY>")?),
+                    };
+                    let mut limits = ExploitLimits::new(penalty);
+                    if let Some(separation) = values.next() {
+                        limits.max_joint_separation = expect_res(separation.parse::<f32>(), "Invalid <SEPARATION>")?;
+                        limits.max_ground_penetration =
+                            expect_res(expect(values.next(), "Expected <PENETRATION>")?.parse::<f32>(), "Invalid <PENETRATION>")?;
+                        limits.max_energy_gain =
+                            expect_res(expect(values.next(), "Expected <ENERGY_GAIN>")?.parse::<f32>(), "Invalid <ENERGY_GAIN>")?;
+                    }
+                    train_config.exploit_limits = Some(limits);
+                } else if arg == "--energy-budget" {
+                    train_config.energy_budget =
+                        Some(expect_res(expect(opts.next(), "Expected <WORK>")?.parse::<f32>(), "Invalid <WORK>")?);
+                } else if arg == "--actuation" {
+                    let strength = expect(opts.next(), "Expected <STRENGTH>")?;
+                    train_config.actuation = Some(expect(ActuationStrength::from_name(strength), "Invalid <STRENGTH>")?);
+                } else if arg == "-j" || arg == "--migration" {
+                    train_config.migration_interval =
+                        expect_res(expect(opts.next(), "Expected <INTERVAL>")?.parse::<usize>(), "Invalid <INTERVAL>")?;
+                } else if arg == "--migrants" {
+                    train_config.migrants = expect_res(expect(opts.next(), "Expected <COUNT>")?.parse::<usize>(), "Invalid <COUNT>")?;
+                } else if arg == "--island-settings" {
+                    let path = expect(opts.next(), "Expected <ISLAND_FILE>")?;
+                    train_config.island_settings = expect_res(IslandSettings::load(path), "Invalid <ISLAND_FILE>")?;
                 }
             }
         }
 
+        // MAP-Elites breeds from its grid, leaving species nothing to share
+        if train_config.map_elites.is_some() && train_config.speciation.is_some() {
+            return err("--speciate can't be used with --map-elites");
+        }
+
+        // Matches aren't measured for physics exploits
+        if train_config.exploit_limits.is_some() && train_config.fitness_fn == "compete" {
+            return err("--exploits can't be used with the compete fitness");
+        }
+
+        // Only these fitnesses are made of weighted terms
+        let weighted = matches!(train_config.fitness_fn.as_str(), "walk" | "straight" | "push" | "compete" | "composite");
+        let stage_weights = train_config.curriculum.as_ref().is_some_and(|c| c.stages.iter().any(|s| s.fitness_weights.is_some()));
+        if stage_weights && !weighted {
+            return err("<CURRICULUM_FILE> sets fitness weights, which <FITNESS_FN> has none of");
+        }
+
         println!();
         println!("Training with the following config: ");
         println!("    session = {}", train_config.session);
@@ -169,6 +471,65 @@ fn parse_args(args: Vec<String>) -> Result<(), InvalidUsageError> {
         println!("    elitism = {}", train_config.elitism);
         println!("    rand_percent = {}", train_config.rand_percent);
         println!("    fitness = {}", train_config.fitness_fn);
+        if let Some(weights) = &train_config.fitness_weights {
+            println!("    fitness_weights = {:?}", weights);
+        }
+        if let Some(heading) = train_config.heading {
+            println!("    heading = {}", heading);
+        }
+        println!("    controller = {}", train_config.controller);
+        println!("    selection = {}", train_config.selection);
+        println!("    hall_of_fame = {}", train_config.hall_of_fame);
+        if let Some(interval) = train_config.cache {
+            println!("    cache = {}", interval);
+        }
+        if let Some(interval) = train_config.trajectories {
+            println!("    trajectories = {}", interval);
+        }
+        if train_config.trials > 1 {
+            println!("    trials = {}", train_config.trials);
+            println!("    aggregation = {:?}", train_config.aggregation);
+        }
+        if train_config.perturbation != TrialPerturbation::default() {
+            println!("    perturbation = {:?}", train_config.perturbation);
+        }
+        if let Some(limits) = &train_config.exploit_limits {
+            println!("    exploit_limits = {:?}", limits);
+        }
+        if let Some(budget) = train_config.energy_budget {
+            println!("    energy_budget = {}", budget);
+        }
+        if let Some(actuation) = train_config.actuation {
+            println!("    actuation = {:?}", actuation);
+        }
+        if let Some(novelty) = train_config.novelty {
+            println!("    novelty = {}", novelty);
+        }
+        if let Some(traits) = &train_config.map_elites {
+            println!("    map_elites = {:?}", traits);
+        }
+        if let Some(threshold) = train_config.speciation {
+            println!("    speciation = {}", threshold);
+        }
+        if train_config.islands > 1 {
+            println!("    islands = {}", train_config.islands);
+            println!("    migration_interval = {}", train_config.migration_interval);
+            println!("    migrants = {}", train_config.migrants);
+            for (i, settings) in train_config.island_settings.iter().enumerate().take(train_config.islands) {
+                println!("    island_{} = {:?}", i, settings);
+            }
+        }
+        if train_config.fitness_fn == "compete" {
+            println!("    opponents = {}", train_config.opponents);
+            println!("    champions = {}", train_config.champions);
+        }
+        match &train_config.environment {
+            Some(environment) => println!("    environment = {:?}", environment),
+            None => println!("    terrain = {}", train_config.terrain),
+        }
+        if let Some(curriculum) = &train_config.curriculum {
+            println!("    curriculum = {} stages", curriculum.stages.len());
+        }
         println!();
 
         train::train(train_config);
@@ -192,6 +553,25 @@ fn parse_args(args: Vec<String>) -> Result<(), InvalidUsageError> {
                 } else if arg == "-b" || arg == "--best" {
                     supplied_mode = true;
                     playback_config.mode = PlaybackMode::BestCreature(0);
+                } else if arg == "-p" || arg == "--pareto" {
+                    supplied_mode = true;
+                    playback_config.mode = PlaybackMode::ParetoFront;
+                } else if arg == "-l" || arg == "--hall-of-fame" {
+                    supplied_mode = true;
+                    playback_config.mode = PlaybackMode::HallOfFame;
+                } else if arg == "-i" || arg == "--island" {
+                    supplied_mode = true;
+                    playback_config.mode =
+                        PlaybackMode::Island(expect_res(expect(opts.next(), "Expected <ISLAND>")?.parse::<usize>(), "Invalid <ISLAND>")?);
+                } else if arg == "-x" || arg == "--elites" {
+                    supplied_mode = true;
+                    let cell = expect(opts.next(), "Expected <CELL>")?;
+                    playback_config.mode = if cell == "all" {
+                        PlaybackMode::Elites(None)
+                    } else {
+                        let cell: Result<Vec<_>, _> = cell.split(',').map(|i| i.parse::<usize>()).collect();
+                        PlaybackMode::Elites(Some(expect_res(cell, "Invalid <CELL>")?))
+                    };
                 } else if arg == "-a" || arg == "--auto-cycle" {
                     playback_config.auto_cycle = Some(Duration::from_secs_f32(expect_res(
                         expect(opts.next(), "Expected <CYCLE_DELAY>")?.parse::<f32>(),
@@ -202,7 +582,11 @@ fn parse_args(args: Vec<String>) -> Result<(), InvalidUsageError> {
         }
 
         if !supplied_mode {
-            return err("Invalid usage, expected [-c|-g|-b]");
+            return err("Invalid usage, expected [-c|-g|-b|-l|-p|-i|-x]");
+        }
+
+        if let Some(environment) = write::load_environment(&playback_config.session) {
+            playback_config.environment = environment;
         }
 
         if let PlaybackMode::BestCreature(_) = playback_config.mode {
@@ -215,6 +599,11 @@ fn parse_args(args: Vec<String>) -> Result<(), InvalidUsageError> {
             PlaybackMode::Creature(id) => ("creature", format!("{}", id)),
             PlaybackMode::Generation => ("generation", "N/A".to_string()),
             PlaybackMode::BestCreature(id) => ("best_creature", format!("{}", id)),
+            PlaybackMode::HallOfFame => ("hall_of_fame", "N/A".to_string()),
+            PlaybackMode::ParetoFront => ("pareto_front", "N/A".to_string()),
+            PlaybackMode::Island(island) => ("island", format!("{}", island)),
+            PlaybackMode::Elites(None) => ("elites", "N/A".to_string()),
+            PlaybackMode::Elites(Some(ref cell)) => ("elites", format!("{:?}", cell)),
             PlaybackMode::List(_) => unreachable!(),
         };
 
@@ -226,8 +615,41 @@ fn parse_args(args: Vec<String>) -> Result<(), InvalidUsageError> {
             Some(duration) => println!("    auto-cycle = {}", duration.as_secs_f32()),
             None => println!("    auto-cycle = false"),
         }
+        println!("    environment = {:?}", playback_config.environment);
         println!();
 
+        if let PlaybackMode::HallOfFame = playback_config.mode {
+            println!("Hall of fame");
+            for (i, entry) in write::load_hall_of_fame(&playback_config.session).iter().enumerate() {
+                println!(
+                    "    {}: id = {}, fitness = {}, generation = {}",
+                    i + 1,
+                    entry.creature.creature.0,
+                    entry.fitness,
+                    entry.generation
+                );
+            }
+            println!();
+        }
+
+        if let PlaybackMode::ParetoFront = playback_config.mode {
+            let (generation, front) = write::load_pareto_front(&playback_config.session).map_err(InvalidUsageError)?;
+            println!("Pareto front of generation {}", generation);
+            for (i, creature) in front.iter().enumerate() {
+                println!("    {}: id = {}", i + 1, creature.creature.0);
+            }
+            println!();
+        }
+
+        if let PlaybackMode::Elites(_) = playback_config.mode {
+            let map_elites = expect(write::load_map_elites(&playback_config.session), "Session was not trained with MAP-Elites")?;
+            println!("MAP-Elites cells over {:?}", map_elites.dimensions.iter().map(|d| d.feature).collect::<Vec<_>>());
+            for (cell, elite) in map_elites.cells.iter() {
+                println!("    {:?}: id = {}, fitness = {}", cell, elite.creature.creature.0, elite.fitness);
+            }
+            println!();
+        }
+
         playback::play(playback_config);
     } else if args[1] == "plist" {
         let list = expect(args.get(2), "Expected [playlist|-l]")?.clone();
@@ -328,6 +750,54 @@ fn parse_args(args: Vec<String>) -> Result<(), InvalidUsageError> {
                 }
             }
         }
+    } else if args[1] == "trajectory" {
+        let session = expect(args.get(2), "Expected [session]")?.clone();
+        let (mut generation, mut creature, mut export) = (None, None, None);
+        let mut opts = args[3..].iter();
+        while let Some(arg) = opts.next() {
+            if arg == "-g" || arg == "--generation" {
+                generation =
+                    Some(expect_res(expect(opts.next(), "Expected <GENERATION_ID>")?.parse::<usize>(), "Invalid <GENERATION_ID>")?);
+            } else if arg == "-c" || arg == "--creature" {
+                creature = Some(expect_res(expect(opts.next(), "Expected <CREATURE_ID>")?.parse::<usize>(), "Invalid <CREATURE_ID>")?);
+            } else if arg == "-e" || arg == "--export" {
+                export = Some(expect(opts.next(), "Expected <FILE>")?.clone());
+            }
+        }
+
+        let (generation, mut trajectories) = write::load_trajectories(&session, generation).map_err(InvalidUsageError)?;
+        trajectories.retain(|trajectory| creature.map_or(true, |id| trajectory.creature == id));
+        if trajectories.is_empty() {
+            return err("No trajectories recorded for this creature");
+        }
+
+        match export {
+            Some(file) => {
+                expect_res(fs::write(&file, Trajectory::to_csv(&trajectories)), "Unable to write <FILE>")?;
+                println!("INFO: exported {} trajectories of generation {} to {}", trajectories.len(), generation, file);
+            },
+            None => {
+                println!("--- Trajectories of generation {} ---", generation);
+                println!();
+                for trajectory in trajectories.iter() {
+                    let summary = trajectory.summary();
+                    println!(
+                        "id: [{}]  trial: [{}]  fitness: [{}]  steps: [{}]  displacement: [{:.3}]  max_height: [{:.3}]  contact: [{:.3}]  \
+                         speed: [{:.3}]  work: [{:.3}]  turn: [{:.3}]",
+                        trajectory.creature,
+                        trajectory.trial,
+                        trajectory.fitness,
+                        trajectory.steps.len(),
+                        summary.displacement,
+                        summary.max_height,
+                        summary.mean_contact,
+                        summary.mean_speed,
+                        summary.work,
+                        summary.turn
+                    );
+                }
+            },
+        }
     } else {
         return err("Invalid first argument");
     }
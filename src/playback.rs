@@ -3,7 +3,7 @@ use std::{
     time::{Duration, Instant},
 };
 
-use behavior_evolver::evolution::{generation::GenerationTestingConfig, write, CreatureEnvironmentPlugin, GroundMarker};
+use behavior_evolver::evolution::{generation::GenerationTestingConfig, write, CreatureEnvironmentPlugin, EnvironmentConfig, GroundMarker};
 use bevy::prelude::*;
 use bevy_rapier3d::{
     dynamics::Velocity,
@@ -15,6 +15,12 @@ pub enum PlaybackMode {
     Creature(usize),
     Generation,
     BestCreature(usize),
+    /// The fittest creatures of the whole session
+    HallOfFame,
+    ParetoFront,
+    Island(usize),
+    /// Every cell of the MAP-Elites grid, or a single cell
+    Elites(Option<Vec<usize>>),
     List(String),
 }
 
@@ -24,23 +30,31 @@ pub struct PlaybackConfig {
     pub mode: PlaybackMode,
     pub auto_cycle: Option<Duration>,
     pub wait_for_fall_timeout: usize,
+    pub environment: EnvironmentConfig,
 }
 
 impl Default for PlaybackConfig {
     fn default() -> Self {
-        Self { session: String::from("default-session"), mode: PlaybackMode::Creature(0), auto_cycle: None, wait_for_fall_timeout: 300 }
+        Self {
+            session: String::from("default-session"),
+            mode: PlaybackMode::Creature(0),
+            auto_cycle: None,
+            wait_for_fall_timeout: 300,
+            environment: EnvironmentConfig::default(),
+        }
     }
 }
 
 
 pub fn play(conf: PlaybackConfig) {
+    let environment = conf.environment.clone();
     App::new()
         .insert_resource(conf)
         .insert_resource(PlaybackCreatures(Vec::new(), 0, Instant::now(), true))
         .add_systems(Startup, setup)
         .add_systems(Update, cycle_creature)
         .add_plugins(DefaultPlugins)
-        .add_plugins(CreatureEnvironmentPlugin { window: true })
+        .add_plugins(CreatureEnvironmentPlugin { window: true, environment })
         .run();
 }
 
@@ -52,8 +66,10 @@ fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
+    mut build_conf: ResMut<CreatureBuilderConfig>,
     conf: Res<PlaybackConfig>,
 ) {
+    write::load_behavior_settings(&conf.session, &mut build_conf);
     match &conf.mode {
         PlaybackMode::Creature(id) | PlaybackMode::BestCreature(id) => {
             let morph = write::load_creature(&conf.session, *id);
@@ -73,6 +89,48 @@ fn setup(
             commands.insert_resource(WaitingForFall(false, 0, 0));
             commands.insert_resource(GenerationTestingConfig { wait_for_fall_timeout: conf.wait_for_fall_timeout, ..Default::default() });
         },
+        PlaybackMode::HallOfFame => {
+            let morphs: Vec<_> = write::load_hall_of_fame(&conf.session).into_iter().map(|entry| entry.creature).collect();
+            let mut res = morphs.first().expect("The hall of fame is empty").evaluate();
+            res.align_to_ground();
+            res.build(&mut commands, &mut meshes, &mut materials, Color::rgba_u8(243, 139, 168, 220));
+            commands.insert_resource(PlaybackCreatures(morphs, 0, Instant::now(), true));
+            commands.insert_resource(WaitingForFall(false, 0, 0));
+            commands.insert_resource(GenerationTestingConfig { wait_for_fall_timeout: conf.wait_for_fall_timeout, ..Default::default() });
+        },
+        PlaybackMode::ParetoFront => {
+            let (_, morphs) = write::load_pareto_front(&conf.session).expect("Unable to load the Pareto front");
+            let mut res = morphs[0].evaluate();
+            res.align_to_ground();
+            res.build(&mut commands, &mut meshes, &mut materials, Color::rgba_u8(243, 139, 168, 220));
+            commands.insert_resource(PlaybackCreatures(morphs, 0, Instant::now(), true));
+            commands.insert_resource(WaitingForFall(false, 0, 0));
+            commands.insert_resource(GenerationTestingConfig { wait_for_fall_timeout: conf.wait_for_fall_timeout, ..Default::default() });
+        },
+        PlaybackMode::Island(island) => {
+            let morphs = write::load_island(&conf.session, *island);
+            let mut res = morphs.first().expect("No creatures on the given island").evaluate();
+            res.align_to_ground();
+            res.build(&mut commands, &mut meshes, &mut materials, Color::rgba_u8(243, 139, 168, 220));
+            commands.insert_resource(PlaybackCreatures(morphs, 0, Instant::now(), true));
+            commands.insert_resource(WaitingForFall(false, 0, 0));
+            commands.insert_resource(GenerationTestingConfig { wait_for_fall_timeout: conf.wait_for_fall_timeout, ..Default::default() });
+        },
+        PlaybackMode::Elites(cell) => {
+            let map_elites = write::load_map_elites(&conf.session).expect("Session was not trained with MAP-Elites");
+            let morphs: Vec<_> = map_elites
+                .cells
+                .into_iter()
+                .filter(|(key, _)| cell.as_ref().map_or(true, |cell| cell == key))
+                .map(|(_, elite)| elite.creature)
+                .collect();
+            let mut res = morphs.first().expect("No elite in the given cell").evaluate();
+            res.align_to_ground();
+            res.build(&mut commands, &mut meshes, &mut materials, Color::rgba_u8(243, 139, 168, 220));
+            commands.insert_resource(PlaybackCreatures(morphs, 0, Instant::now(), true));
+            commands.insert_resource(WaitingForFall(false, 0, 0));
+            commands.insert_resource(GenerationTestingConfig { wait_for_fall_timeout: conf.wait_for_fall_timeout, ..Default::default() });
+        },
         PlaybackMode::List(playlist) => {
             let morphs = write::load_list(playlist);
             let mut res = morphs[0].evaluate();
@@ -89,6 +147,7 @@ fn setup(
 #[derive(Resource)]
 struct WaitingForFall(bool, usize, usize);
 
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn cycle_creature(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -106,6 +165,7 @@ fn cycle_creature(
     mut build_conf: ResMut<CreatureBuilderConfig>,
     time: Res<Time>,
     mut just_spawned_creature: Local<bool>,
+    environment: Res<EnvironmentConfig>,
 ) {
     if *just_spawned_creature {
         for (entity, _, _, mut friction, mut restitution, _, _) in limbs.iter_mut() {
@@ -167,7 +227,7 @@ fn cycle_creature(
                     mat.base_color = Color::rgba_u8(137, 220, 235, 220);
                 }
                 for mut friction in ground.iter_mut() {
-                    friction.coefficient = 0.3;
+                    friction.coefficient = environment.ground_friction;
                 }
                 limb_info_save.clear();
             }
@@ -2,15 +2,32 @@ use std::fs;
 
 use behavior_evolver::{
     evolution::{
-        fitness::{jump::JumpFitnessEval, walk::WalkFitnessEval},
+        cache::FitnessCache,
+        competition::{CompetitionPlugin, Tournament, TournamentOpponents},
+        curriculum::Curriculum,
+        diagnostics::ExploitLimits,
+        fitness::{
+            climb::ClimbFitnessEval, compete::CompetitionFitnessEval, composite::CompositeFitnessEval,
+            directional::DirectionalWalkFitnessEval, jump::JumpFitnessEval, push::PushFitnessEval, swim::SwimFitnessEval,
+            walk::WalkFitnessEval,
+        },
         generation::GenerationTestingConfig,
-        populate::GenerationPopulator,
+        island::{IslandModel, IslandSettings},
+        map_elites::{CreatureTrait, EliteDimension, MapElites},
+        novelty::NoveltySearch,
+        populate::{GenerationPopulator, SelectionMethod},
+        push::PushTask,
+        species::Speciation,
         state::{EvolutionState, EvolutionTrainingEvent},
-        write, CreatureEvolutionPlugin,
+        terrain::Terrain,
+        trajectory::TrajectoryRecorder,
+        trials::{TrialAggregation, TrialPerturbation},
+        write, CreatureEvolutionPlugin, EnvironmentConfig,
     },
-    mutate::{MutateMorphologyParams, RandomMorphologyParams},
+    mutate::{CreatureControllerType, MutateMorphologyParams, RandomMorphologyParams},
 };
 use bevy::prelude::*;
+use creature_builder::{config::CreatureBuilderConfig, ActuationStrength};
 use indicatif::{ProgressBar, ProgressStyle};
 use termion::{color, style};
 
@@ -26,6 +43,55 @@ pub struct TrainConfig {
     pub pop_size: usize,
     pub num_mutations: usize,
     pub fitness_fn: String,
+    /// The weights of the terms of a composite fitness, if given
+    pub fitness_weights: Option<Vec<f32>>,
+    /// The direction directional fitnesses reward moving in, in degrees from +x
+    /// towards +z, if not +x
+    pub heading: Option<f32>,
+    pub controller: String,
+    pub terrain: String,
+    /// Replaces the environment chosen from the fitness function and terrain
+    pub environment: Option<EnvironmentConfig>,
+    pub curriculum: Option<Curriculum>,
+    pub selection: String,
+    /// The weight of novelty in selection, if novelty search is enabled
+    pub novelty: Option<f32>,
+    /// The traits of the MAP-Elites grid, if MAP-Elites is used
+    pub map_elites: Option<Vec<CreatureTrait>>,
+    /// The genotype distance threshold of a species, if speciation is used
+    pub speciation: Option<f32>,
+    /// The number of islands, each with its own population of `pop_size`
+    pub islands: usize,
+    /// The number of generations between migrations between islands
+    pub migration_interval: usize,
+    /// The number of creatures each island sends to the next when migrating
+    pub migrants: usize,
+    /// The settings of each island that differ from the shared ones
+    pub island_settings: Vec<IslandSettings>,
+    /// The number of opponents each creature plays when competing
+    pub opponents: usize,
+    /// Whether competing creatures play the champions of past generations
+    /// instead of each other
+    pub champions: bool,
+    /// The number of the fittest creatures of the session to keep
+    pub hall_of_fame: usize,
+    /// Reuses the fitness of creatures that were already tested, testing them
+    /// again every this many generations unless 0, if set
+    pub cache: Option<usize>,
+    /// Records the state of every tested creature every this many steps, if set
+    pub trajectories: Option<usize>,
+    /// The number of times each creature is tested
+    pub trials: usize,
+    pub perturbation: TrialPerturbation,
+    pub aggregation: TrialAggregation,
+    /// Penalizes creatures that exploit the physics, if set
+    pub exploit_limits: Option<ExploitLimits>,
+    /// The work each creature can do in a test before its effectors are
+    /// disabled, if limited
+    pub energy_budget: Option<f32>,
+    /// How the force limit of each joint scales with its limbs, or the scaling
+    /// the session was trained with if unset
+    pub actuation: Option<ActuationStrength>,
 }
 
 impl Default for TrainConfig {
@@ -41,10 +107,42 @@ impl Default for TrainConfig {
             pop_size: 250,
             num_mutations: 80,
             fitness_fn: String::from("jump"),
+            fitness_weights: None,
+            heading: None,
+            controller: String::from("expr"),
+            terrain: String::from("flat"),
+            environment: None,
+            curriculum: None,
+            selection: String::from("elitism"),
+            novelty: None,
+            map_elites: None,
+            speciation: None,
+            islands: 1,
+            migration_interval: 10,
+            migrants: 2,
+            island_settings: Vec::new(),
+            opponents: 4,
+            champions: false,
+            hall_of_fame: 10,
+            cache: None,
+            trajectories: None,
+            trials: 1,
+            perturbation: TrialPerturbation::default(),
+            aggregation: TrialAggregation::default(),
+            exploit_limits: None,
+            energy_budget: None,
+            actuation: None,
         }
     }
 }
 
+impl TrainConfig {
+    /// The heading as a direction along the ground, as (x, z), if one was given
+    fn heading_direction(&self) -> Option<Vec2> {
+        self.heading.map(|degrees| Vec2::from_angle(degrees.to_radians()))
+    }
+}
+
 pub fn train(conf: TrainConfig) {
     let mut app = App::new();
     app.add_systems(Startup, setup);
@@ -65,10 +163,42 @@ pub fn train(conf: TrainConfig) {
         app.add_plugins(MinimalPlugins).add_plugins(bevy::transform::TransformPlugin).add_plugins(bevy::hierarchy::HierarchyPlugin);
     }
 
+    let environment = match &conf.environment {
+        Some(environment) => environment.clone(),
+        None if conf.fitness_fn == "swim" => EnvironmentConfig::water(),
+        None => EnvironmentConfig::default().with_terrain(Terrain::from_name(&conf.terrain).expect("Invalid terrain")),
+    };
+    let heading = conf.heading_direction();
     if conf.fitness_fn == "jump" {
-        app.add_plugins(CreatureEvolutionPlugin::<JumpFitnessEval>::new(conf.visual));
+        app.add_plugins(CreatureEvolutionPlugin::<JumpFitnessEval>::new(conf.visual).with_environment(environment));
     } else if conf.fitness_fn == "walk" {
-        app.add_plugins(CreatureEvolutionPlugin::<WalkFitnessEval>::new(conf.visual));
+        app.add_plugins(CreatureEvolutionPlugin::<WalkFitnessEval>::new(conf.visual).with_environment(environment));
+    } else if conf.fitness_fn == "straight" {
+        app.add_plugins(
+            CreatureEvolutionPlugin::<DirectionalWalkFitnessEval>::new(conf.visual)
+                .with_environment(environment)
+                .with_fitness_heading(heading),
+        );
+    } else if conf.fitness_fn == "climb" {
+        app.add_plugins(CreatureEvolutionPlugin::<ClimbFitnessEval>::new(conf.visual).with_environment(environment));
+    } else if conf.fitness_fn == "push" {
+        app.add_plugins(
+            CreatureEvolutionPlugin::<PushFitnessEval>::new(conf.visual).with_environment(environment).with_fitness_heading(heading),
+        );
+    } else if conf.fitness_fn == "swim" {
+        app.add_plugins(CreatureEvolutionPlugin::<SwimFitnessEval>::new(conf.visual).with_environment(environment));
+    } else if conf.fitness_fn == "compete" {
+        let opponents =
+            if conf.champions { TournamentOpponents::HallOfFame(conf.opponents) } else { TournamentOpponents::Sample(conf.opponents) };
+        app.add_plugins(CreatureEvolutionPlugin::<CompetitionFitnessEval>::new(conf.visual).with_environment(environment))
+            .add_plugins(CompetitionPlugin::new(Tournament::new(opponents)));
+    } else if conf.fitness_fn == "composite" {
+        app.add_plugins(
+            CreatureEvolutionPlugin::<CompositeFitnessEval>::new(conf.visual)
+                .with_environment(environment)
+                .with_fitness_weights(conf.fitness_weights.clone().unwrap_or_default())
+                .with_fitness_heading(heading),
+        );
     } else {
         panic!("Invalid fitness function");
     }
@@ -90,6 +220,7 @@ fn print_info(
     mut bar: ResMut<GenerationProgressBar>,
     conf: Res<TrainConfig>,
     populator: Res<GenerationPopulator>,
+    curriculum: Res<Curriculum>,
 ) {
     for ev in train_evr.read() {
         match ev {
@@ -111,29 +242,93 @@ fn print_info(
                     populator.best_creature,
                     color::Fg(color::Reset),
                 ));
+                if let Some(stage) = curriculum.current_stage() {
+                    template.push_str(&format!(
+                        "--- Curriculum stage: {}{}/{}{} ({}) ---\n",
+                        color::Fg(color::Yellow),
+                        curriculum.current + 1,
+                        curriculum.stages.len(),
+                        color::Fg(color::Reset),
+                        stage.name,
+                    ));
+                }
                 template.push_str("{spinner:.green} [{elapsed}] [{bar:50.white/blue}] {pos}/{len} ({eta})");
 
-                bar.0 = ProgressBar::new(conf.pop_size as u64)
+                bar.0 = ProgressBar::new((conf.pop_size * conf.islands) as u64)
                     .with_style(ProgressStyle::with_template(&template).unwrap().progress_chars("=> ").tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏"));
             },
         }
     }
 }
 
-fn setup(mut commands: Commands, mut state: ResMut<NextState<EvolutionState>>, conf: Res<TrainConfig>) {
+fn setup(
+    mut commands: Commands,
+    mut state: ResMut<NextState<EvolutionState>>,
+    mut build_conf: ResMut<CreatureBuilderConfig>,
+    conf: Res<TrainConfig>,
+) {
+    build_conf.behavior.energy_budget = conf.energy_budget;
+    build_conf.behavior.actuation = conf.actuation;
+    if let Some(curriculum) = &conf.curriculum {
+        commands.insert_resource(curriculum.clone());
+    }
     commands.insert_resource(GenerationTestingConfig {
         test_time: conf.test_time,
         session: conf.session.clone(),
         wait_for_fall: true,
+        trials: conf.trials,
+        perturbation: conf.perturbation,
+        aggregation: conf.aggregation,
+        push: (conf.fitness_fn == "push").then(|| PushTask::default().with_heading(conf.heading_direction().unwrap_or(Vec2::X))),
         ..Default::default()
     });
-    commands.insert_resource(GenerationPopulator::new(
+    if let Some(interval) = conf.trajectories {
+        commands.insert_resource(TrajectoryRecorder::new(interval));
+    }
+    match conf.cache {
+        Some(0) => commands.insert_resource(FitnessCache::new()),
+        Some(interval) => commands.insert_resource(FitnessCache::new().with_reevaluation(interval)),
+        None => (),
+    }
+    commands.insert_resource(build_populator(&conf));
+    if conf.islands > 1 {
+        let islands = (0..conf.islands)
+            .map(|i| match conf.island_settings.get(i) {
+                Some(settings) => settings.apply(build_populator(&conf)),
+                None => build_populator(&conf),
+            })
+            .collect();
+        commands.insert_resource(IslandModel::new(islands).with_migration(conf.migration_interval, conf.migrants));
+    }
+    state.set(EvolutionState::BeginTrainingSession);
+}
+
+
+fn build_populator(conf: &TrainConfig) -> GenerationPopulator {
+    let mut populator = GenerationPopulator::new(
         conf.elitism,
         conf.rand_percent,
         conf.pop_size,
         MutateMorphologyParams::default(),
-        RandomMorphologyParams::default(),
+        RandomMorphologyParams {
+            controller: if conf.controller == "neural" { CreatureControllerType::Neural } else { CreatureControllerType::Expr },
+            ..Default::default()
+        },
         conf.num_mutations,
-    ));
-    state.set(EvolutionState::BeginTrainingSession);
+    )
+    .with_selection(if conf.selection == "nsga2" { SelectionMethod::Nsga2 } else { SelectionMethod::Elitism })
+    .with_hall_of_fame(conf.hall_of_fame);
+    if let Some(weight) = conf.novelty {
+        populator = populator.with_novelty(NoveltySearch::new(weight));
+    }
+    if let Some(traits) = &conf.map_elites {
+        populator = populator.with_map_elites(MapElites::new(traits.iter().map(|t| EliteDimension::new(*t)).collect()));
+    }
+    if let Some(threshold) = conf.speciation {
+        populator = populator.with_speciation(Speciation::new(threshold));
+    }
+    if let Some(limits) = conf.exploit_limits {
+        populator = populator.with_exploit_limits(limits);
+    }
+    populator
 }